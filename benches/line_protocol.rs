@@ -0,0 +1,92 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use influxdb2::models::{DataPoint, WriteDataPoint};
+use influxdb2::writable::{write_measurement_to, FieldsWritable, TagsWritable, TimestampWritable};
+use influxdb2_derive::WriteDataPoint;
+
+#[derive(WriteDataPoint)]
+#[measurement = "cpu"]
+struct Cpu {
+    #[influxdb(tag)]
+    host: String,
+    #[influxdb(tag)]
+    region: &'static str,
+    usage: f64,
+    cores: i64,
+    #[influxdb(timestamp)]
+    time: i64,
+}
+
+fn points() -> Vec<Cpu> {
+    (0..1000)
+        .map(|i| Cpu {
+            host: format!("server{:02}", i % 20),
+            region: "us west",
+            usage: i as f64 / 7.0,
+            cores: i % 64,
+            time: 1_600_000_000_000_000_000 + i,
+        })
+        .collect()
+}
+
+fn encode(c: &mut Criterion) {
+    let points = points();
+    let mut group = c.benchmark_group("encode 1000 points");
+
+    group.bench_function("derive", |b| {
+        let mut buf = Vec::with_capacity(64 * 1024);
+        b.iter(|| {
+            buf.clear();
+            for p in &points {
+                p.write_data_point_to(&mut buf).unwrap();
+            }
+            black_box(&buf);
+        })
+    });
+
+    group.bench_function("tuples", |b| {
+        let mut buf = Vec::with_capacity(64 * 1024);
+        b.iter(|| {
+            buf.clear();
+            for p in &points {
+                write_measurement_to("cpu", &mut buf).unwrap();
+                buf.push(b',');
+                ("host", p.host.as_str(), "region", p.region)
+                    .write_tags_to(&mut buf)
+                    .unwrap();
+                buf.push(b' ');
+                ("usage", p.usage, "cores", p.cores)
+                    .write_fields_to(&mut buf)
+                    .unwrap();
+                buf.push(b' ');
+                p.time.write_timestamp_to(&mut buf).unwrap();
+                buf.push(b'\n');
+            }
+            black_box(&buf);
+        })
+    });
+
+    group.bench_function("data point", |b| {
+        let mut buf = Vec::with_capacity(64 * 1024);
+        b.iter(|| {
+            buf.clear();
+            for p in &points {
+                DataPoint::builder("cpu")
+                    .tag("host", p.host.as_str())
+                    .tag("region", p.region)
+                    .field("usage", p.usage)
+                    .field("cores", p.cores)
+                    .timestamp(p.time)
+                    .build()
+                    .unwrap()
+                    .write_data_point_to(&mut buf)
+                    .unwrap();
+            }
+            black_box(&buf);
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, encode);
+criterion_main!(benches);
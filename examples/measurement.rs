@@ -1,3 +1,4 @@
+use influxdb2::api::schema::TimeRange;
 use std::error::Error;
 
 #[tokio::main]
@@ -8,16 +9,14 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let token = std::env::var("INFLUXDB2_TOKEN").unwrap();
 
     let client = influxdb2::Client::new(influx_url, org, token);
+    let range = TimeRange::new(chrono::Duration::days(-365), chrono::Duration::days(-1));
 
-    let measurements = client
-        .list_measurements(bucket, Some("-365d"), Some("-1d"))
-        .await
-        .unwrap();
+    let measurements = client.measurements(bucket, &range).await.unwrap();
     println!("measurements: {:?}", measurements);
 
     for m in measurements.iter() {
         let field_keys = client
-            .list_measurement_field_keys(bucket, m, Some("-365d"), Some("now()"))
+            .measurement_field_keys(bucket, m, &range)
             .await
             .unwrap();
         println!("field keys: {:?}", field_keys);
@@ -25,7 +24,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     for m in measurements.iter() {
         let tag_values = client
-            .list_measurement_tag_values(bucket, m, "host", Some("-365d"), None)
+            .measurement_tag_values(bucket, m, "host", &range)
             .await;
         println!(
             "tag values for measurement {} and tag host: {:?}",
@@ -34,13 +33,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
     }
 
     for m in measurements.iter() {
-        let tag_values = client
-            .list_measurement_tag_keys(bucket, m, Some("-365d"), None)
-            .await;
-        println!(
-            "tag values for measurement {} and tag host: {:?}",
-            &m, tag_values
-        );
+        let tag_keys = client.measurement_tag_keys(bucket, m, &range).await;
+        println!("tag keys for measurement {}: {:?}", &m, tag_keys);
     }
 
     Ok(())
@@ -0,0 +1,87 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, ItemStruct};
+
+use crate::expand_writable::{krate, write_data_point, FieldType, StructWritable};
+
+pub fn impl_model(tokens: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(tokens as ItemStruct);
+    let input = StructWritable::from(input);
+
+    let write_data_point = write_data_point(&input);
+    let from_map = from_map(&input);
+    let model = model(&input);
+
+    let output = quote! {
+        #write_data_point
+        #from_map
+        #model
+    };
+    output.into()
+}
+
+/// Implements `FromMap`, reading every member from the column it is
+/// written to. Members missing from a row keep their default value.
+fn from_map(input: &StructWritable) -> TokenStream2 {
+    let krate = krate();
+    let ident = &input.ident;
+    let generics = &input.generics;
+
+    let assignments: Vec<TokenStream2> = input
+        .fields
+        .iter()
+        .map(|f| {
+            let member = &f.ident;
+            let kind = &f.kind;
+            let key = match f.field_type {
+                FieldType::Timestamp => "_time",
+                FieldType::Measurement => "_measurement",
+                _ => &f.name,
+            };
+            quote! {
+                if let Some(v) = <#kind as #krate::model::ValueReadable>::read_column(map.remove(#key)) {
+                    settings.#member = v;
+                }
+            }
+        })
+        .collect();
+
+    quote! {
+        impl #generics #krate::FromMap for #ident #generics
+        {
+            fn from_genericmap(mut map: #krate::model::GenericMap) -> Self {
+                let mut settings = Self::default();
+                #(
+                    #assignments
+                )*
+                settings
+            }
+        }
+    }
+}
+
+/// Implements `InfluxModel`
+fn model(input: &StructWritable) -> TokenStream2 {
+    let krate = krate();
+    let ident = &input.ident;
+    let generics = &input.generics;
+
+    let measurement = if input.of_type(FieldType::Measurement).next().is_some() {
+        quote!(None)
+    } else {
+        let measurement = &input.measurement;
+        quote!(Some(#measurement))
+    };
+    let tags = input.of_type(FieldType::Tag).map(|f| &f.name);
+    let fields = input.of_type(FieldType::Field).map(|f| &f.name);
+
+    quote! {
+        impl #generics #krate::model::InfluxModel for #ident #generics
+        {
+            const MEASUREMENT: Option<&'static str> = #measurement;
+            const TAGS: &'static [&'static str] = &[#(#tags),*];
+            const FIELDS: &'static [&'static str] = &[#(#fields),*];
+        }
+    }
+}
@@ -1,7 +1,6 @@
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
-use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::{parse_macro_input, ExprTuple, Ident};
 
@@ -26,43 +25,46 @@ fn ident_check(input: ExprTuple) -> Vec<Ident> {
     generic_idents
 }
 
+/// Indexes of the keys and the values of a tuple of key-value pairs
+fn pair_indexes(generic_idents: &[Ident]) -> impl Iterator<Item = (syn::Index, syn::Index)> {
+    (0..generic_idents.len())
+        .step_by(2)
+        .map(|i| (i.into(), (i + 1).into()))
+}
+
 pub fn make_tuple_tags(tokens: TokenStream) -> TokenStream {
     let input = parse_macro_input!(tokens as ExprTuple);
     let generic_idents = ident_check(input);
 
-    let mut concate_str: Vec<TokenStream2> = Vec::new();
-    let enumerate_generic = generic_idents.iter().enumerate().collect::<Vec<_>>();
-    let mut chunks = enumerate_generic.as_slice().chunks(2).peekable();
-    loop {
-        match chunks.next() {
-            Some(c) => {
-                let first_index: syn::Index = c[0].0.into();
-                let second_index: syn::Index = c[1].0.into();
-                concate_str.push(quote!(res.push_str(&self.#first_index.encode_key())));
-                concate_str.push(quote!(res.push_str("=")));
-                concate_str.push(quote!(res.push_str(&self.#second_index.encode_key())));
-
-                if chunks.peek().is_some() {
-                    concate_str.push(quote!(res.push_str(",")))
+    // `None` values are left out, along with their key
+    let pair_writes = pair_indexes(&generic_idents)
+        .map(|(first_index, second_index)| {
+            quote! {
+                if self.#second_index.is_present() {
+                    if !first {
+                        w.write_all(b",")?;
+                    }
+                    first = false;
+                    self.#first_index.write_key_to(&mut w)?;
+                    w.write_all(b"=")?;
+                    self.#second_index.write_key_to(&mut w)?;
                 }
             }
-            None => {
-                break;
-            }
-        };
-    }
+        })
+        .collect::<Vec<_>>();
     let generic_annotate = generic_idents
         .iter()
         .map(|i| quote!(#i: KeyWritable))
         .collect::<Vec<_>>();
     let output = quote! {
         impl <#(#generic_annotate),*> TagsWritable for (#(#generic_idents),*){
-            fn encode_tags(&self) -> String {
-                let mut res = String::new();
+            fn write_tags_to<W: std::io::Write>(&self, mut w: W) -> std::io::Result<()> {
+                let mut first = true;
                 #(
-                    #concate_str;
+                    #pair_writes
                 )*
-                res
+                let _ = first;
+                Ok(())
             }
         }
     };
@@ -73,27 +75,22 @@ pub fn make_tuple_fields(tokens: TokenStream) -> TokenStream {
     let input = parse_macro_input!(tokens as ExprTuple);
     let generic_idents = ident_check(input);
 
-    let mut concate_str: Vec<TokenStream2> = Vec::new();
-    let enumerate_generic = generic_idents.iter().enumerate().collect::<Vec<_>>();
-    let mut chunks = enumerate_generic.as_slice().chunks(2).peekable();
-    loop {
-        match chunks.next() {
-            Some(c) => {
-                let first_index: syn::Index = c[0].0.into();
-                let second_index: syn::Index = c[1].0.into();
-                concate_str.push(quote!(res.push_str(&self.#first_index.encode_key())));
-                concate_str.push(quote!(res.push_str("=")));
-                concate_str.push(quote!(res.push_str(&self.#second_index.encode_value())));
-
-                if chunks.peek().is_some() {
-                    concate_str.push(quote!(res.push_str(",")))
+    // `None` values are left out, along with their key
+    let pair_writes = pair_indexes(&generic_idents)
+        .map(|(first_index, second_index)| {
+            quote! {
+                if self.#second_index.is_present() {
+                    if !first {
+                        w.write_all(b",")?;
+                    }
+                    first = false;
+                    self.#first_index.write_key_to(&mut w)?;
+                    w.write_all(b"=")?;
+                    self.#second_index.write_value_to(&mut w)?;
                 }
             }
-            None => {
-                break;
-            }
-        };
-    }
+        })
+        .collect::<Vec<_>>();
     let generic_annotate = &generic_idents
         .chunks(2)
         .map(|i| {
@@ -104,12 +101,13 @@ pub fn make_tuple_fields(tokens: TokenStream) -> TokenStream {
         .collect::<Vec<_>>();
     let output = quote! {
         impl <#(#generic_annotate),*> FieldsWritable for (#(#generic_idents),*){
-            fn encode_fields(&self) -> String {
-                let mut res = String::new();
+            fn write_fields_to<W: std::io::Write>(&self, mut w: W) -> std::io::Result<()> {
+                let mut first = true;
                 #(
-                    #concate_str;
+                    #pair_writes
                 )*
-                res
+                let _ = first;
+                Ok(())
             }
         }
     };
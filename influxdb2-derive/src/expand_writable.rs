@@ -1,143 +1,186 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::{format_ident, quote};
-use syn::{parse_macro_input, Field, Fields, Ident, ItemStruct, Lit, Meta, MetaNameValue, Type};
+use quote::quote;
+use syn::{
+    parse_macro_input, Field, Fields, Generics, Ident, ItemStruct, Lit, Meta, MetaNameValue,
+    NestedMeta, Type,
+};
 
 const INFLUX_TAG: &str = "influxdb";
 
 pub fn impl_writeable(tokens: TokenStream) -> TokenStream {
-    let krate = krate();
-    let writable_krate = writable_krate();
     let input = parse_macro_input!(tokens as ItemStruct); // only struct is supported now.
-    let ident = input.ident;
-    let generics = input.generics;
-    let measurement = input.attrs.into_iter().find_map(|a| {
-        let is_outer = match a.style {
-            syn::AttrStyle::Outer => true,
-            syn::AttrStyle::Inner(_) => false,
+    write_data_point(&StructWritable::from(input)).into()
+}
+
+/// Struct annotated with `#[measurement]` and `#[influxdb(...)]`
+pub(crate) struct StructWritable {
+    pub(crate) ident: Ident,
+    pub(crate) generics: Generics,
+    /// Measurement name, the struct name unless set by `#[measurement]`
+    pub(crate) measurement: String,
+    pub(crate) fields: Vec<FieldWritable>,
+}
+
+impl StructWritable {
+    pub(crate) fn from(input: ItemStruct) -> Self {
+        let ident = input.ident;
+        let measurement = input.attrs.into_iter().find_map(|a| {
+            let is_outer = match a.style {
+                syn::AttrStyle::Outer => true,
+                syn::AttrStyle::Inner(_) => false,
+            };
+            let is_measurement = a.path.is_ident("measurement");
+            if is_outer && is_measurement {
+                Some(a)
+            } else {
+                None
+            }
+        });
+        let measure_value = measurement.and_then(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(lit_str),
+                ..
+            })) => Some(lit_str.value()),
+            _ => None,
+        });
+
+        let measurement = match measure_value {
+            Some(v) => v,
+            None => ident.to_string(),
         };
-        let is_measurement = a.path.is_ident("measurement");
-        if is_outer && is_measurement {
-            Some(a)
-        } else {
-            None
+
+        let fields: Vec<FieldWritable> = match input.fields {
+            Fields::Named(fields) => fields
+                .named
+                .into_iter()
+                .map(FieldWritable::from)
+                .filter(|field| field.field_type != FieldType::Ignore)
+                .collect(),
+            _ => panic!("a struct without named fields is not supported"),
+        };
+
+        let count = |field_type| fields.iter().filter(|f| f.field_type == field_type).count();
+        if count(FieldType::Timestamp) > 1 {
+            panic!("You can specify at most one #[influxdb(timestamp)] field.")
+        }
+        if count(FieldType::Measurement) > 1 {
+            panic!("You can specify at most one #[influxdb(measurement)] field.")
+        }
+        if count(FieldType::Field) == 0 {
+            panic!("You have to specify at least one #[field] field.")
         }
-    });
-    let measure_value = measurement.and_then(|attr| match attr.parse_meta() {
-        Ok(Meta::NameValue(MetaNameValue {
-            lit: Lit::Str(lit_str),
-            ..
-        })) => Some(lit_str.value()),
-        _ => None,
-    });
 
-    let measure = match measure_value {
-        Some(v) => format_ident!("{}", v).to_string(),
-        None => ident.clone().to_string(),
-    };
-
-    let fields: Vec<FieldWritable> = match input.fields {
-        Fields::Named(fields) => fields
-            .named
-            .into_iter()
-            .filter_map(FieldWritable::from)
-            .filter(|field| match field.field_type {
-                FieldType::Ignore => false,
-                _ => true,
-            })
-            .collect(),
-        _ => panic!("a struct without named fields is not supported"),
-    };
-    let tag_writes: Vec<TokenStream2> = fields
-        .iter()
-        .filter_map(|f| match f.field_type {
-            FieldType::Tag => {
-                let ident = f.ident.clone();
-                let ident_str = ident.to_string();
-                let kind = f.kind.clone();
-                Some(quote! {
-                    w.write_all(format!("{}", #ident_str).as_bytes())?;
+        Self {
+            ident,
+            generics: input.generics,
+            measurement,
+            fields,
+        }
+    }
+
+    pub(crate) fn of_type(&self, field_type: FieldType) -> impl Iterator<Item = &FieldWritable> {
+        self.fields
+            .iter()
+            .filter(move |f| f.field_type == field_type)
+    }
+}
+
+/// Implements `WriteDataPoint`
+pub(crate) fn write_data_point(input: &StructWritable) -> TokenStream2 {
+    let krate = krate();
+    let writable_krate = writable_krate();
+    let ident = &input.ident;
+    let generics = &input.generics;
+    let measure = &input.measurement;
+
+    // The tags are written after the measurement, each one with a leading
+    // comma. `None` values are left out.
+    let tag_writes: Vec<TokenStream2> = input
+        .of_type(FieldType::Tag)
+        .map(|f| {
+            let ident = &f.ident;
+            let name = &f.name;
+            let kind = &f.kind;
+            quote! {
+                if <#kind as #writable_krate::KeyWritable>::is_present(&self.#ident) {
+                    w.write_all(b",")?;
+                    <&str as #writable_krate::KeyWritable>::write_key_to(&#name, &mut w)?;
                     w.write_all(b"=")?;
-                    w.write_all(<#kind as #writable_krate::KeyWritable>::encode_key(&self.#ident).into_bytes().as_slice())?;
-                })
+                    <#kind as #writable_krate::KeyWritable>::write_key_to(&self.#ident, &mut w)?;
+                }
             }
-            _ => None,
         })
         .collect();
 
-    let fields_writes: Vec<TokenStream2> = fields
-        .iter()
-        .filter_map(|f| match f.field_type {
-            FieldType::Field => {
-                let ident = f.ident.clone();
-                let ident_str = ident.to_string();
-                let kind = f.kind.clone();
-                Some(quote! {
-                    w.write_all(format!("{}", #ident_str).as_bytes())?;
+    let fields_writes: Vec<TokenStream2> = input
+        .of_type(FieldType::Field)
+        .map(|f| {
+            let ident = &f.ident;
+            let name = &f.name;
+            let kind = &f.kind;
+            quote! {
+                if <#kind as #writable_krate::ValueWritable>::is_present(&self.#ident) {
+                    w.write_all(if fields == 0 { b" " } else { b"," })?;
+                    fields += 1;
+                    <&str as #writable_krate::KeyWritable>::write_key_to(&#name, &mut w)?;
                     w.write_all(b"=")?;
-                    w.write_all(<#kind as #writable_krate::ValueWritable>::encode_value(&self.#ident).into_bytes().as_slice())?;
-                })
+                    <#kind as #writable_krate::ValueWritable>::write_value_to(&self.#ident, &mut w)?;
+                }
             }
-            _ => None,
         })
         .collect();
 
-    let timestamp_writes: Vec<TokenStream2>  = fields
-        .iter()
-        .filter_map(|f| match f.field_type {
-            FieldType::Timestamp => {
-                let ident = f.ident.clone();
-                let kind = f.kind.clone();
-                Some(quote! {
-                    w.write_all(<#kind as #writable_krate::TimestampWritable>::encode_timestamp(&self.#ident).into_bytes().as_slice())?;
-                })
+    let timestamp_writes: Vec<TokenStream2> = input
+        .of_type(FieldType::Timestamp)
+        .map(|f| {
+            let ident = &f.ident;
+            let kind = &f.kind;
+            quote! {
+                if <#kind as #writable_krate::TimestampWritable>::is_present(&self.#ident) {
+                    w.write_all(b" ")?;
+                    <#kind as #writable_krate::TimestampWritable>::write_timestamp_to(&self.#ident, &mut w)?;
+                }
             }
-            _ => None,
         })
         .collect();
 
-    if tag_writes.len() < 1 {
-        panic!("You have to specify at least one #[tag] field.")
-    }
-    if timestamp_writes.len() != 1 {
-        panic!("You have to specify at exact one #[timestamp] field.")
-    }
-    if fields_writes.len() < 1 {
-        panic!("You have to specify at least one #[field] field.")
-    }
-
-    let mut combined_tag_writes = vec![];
-    for (index, tag_write) in tag_writes.iter().enumerate() {
-        if index > 0 {
-            combined_tag_writes.push(quote!(w.write_all(b",")?;));
-        }
-        combined_tag_writes.push(tag_write.clone());
-    }
+    let measurement_writes: Vec<TokenStream2> = input
+        .of_type(FieldType::Measurement)
+        .map(|f| {
+            let ident = &f.ident;
+            quote! {
+                #writable_krate::write_measurement_to(::std::convert::AsRef::<str>::as_ref(&self.#ident), &mut w)?;
+            }
+        })
+        .collect();
 
-    let mut combined_fields_writes = vec![];
-    for (index, fields_write) in fields_writes.iter().enumerate() {
-        if index > 0 {
-            combined_fields_writes.push(quote!(w.write_all(b",")?;));
+    let measurement_write = measurement_writes.into_iter().next().unwrap_or_else(|| {
+        quote! {
+            #writable_krate::write_measurement_to(#measure, &mut w)?;
         }
-        combined_fields_writes.push(fields_write.clone());
-    }
+    });
 
-    let output = quote! {
+    quote! {
         impl #generics #krate::models::WriteDataPoint for #ident #generics
         {
             fn write_data_point_to<W>(&self,mut w: W) -> std::io::Result<()>
             where
                 W: std::io::Write{
-                w.write_all(format!("{},", #measure).as_bytes())?;
-
+                #measurement_write
                 #(
-                    #combined_tag_writes
+                    #tag_writes
                 )*
-                w.write_all(b" ")?;
+                let mut fields = 0;
                 #(
-                    #combined_fields_writes
+                    #fields_writes
                 )*
-                w.write_all(b" ")?;
+                if fields == 0 {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "a data point must have at least one field that is not None",
+                    ));
+                }
                 #(
                     #timestamp_writes
                 )*
@@ -146,11 +189,10 @@ pub fn impl_writeable(tokens: TokenStream) -> TokenStream {
                 Ok(())
             }
         }
-    };
-    output.into()
+    }
 }
 
-fn krate() -> TokenStream2 {
+pub(crate) fn krate() -> TokenStream2 {
     quote!(::influxdb2)
 }
 
@@ -160,59 +202,69 @@ fn writable_krate() -> TokenStream2 {
 }
 
 #[derive(Debug)]
-struct FieldWritable {
-    field_type: FieldType,
-    kind: Type,
-    ident: Ident,
+pub(crate) struct FieldWritable {
+    pub(crate) field_type: FieldType,
+    pub(crate) kind: Type,
+    pub(crate) ident: Ident,
+    /// Tag or field key, the field name unless renamed
+    pub(crate) name: String,
 }
 
 impl FieldWritable {
-    fn from(value: Field) -> Option<Self> {
+    fn from(value: Field) -> Self {
         let ident = value.ident.unwrap();
 
-        let field_type = value.attrs.iter().find_map(|a| {
-            if a.path.is_ident(INFLUX_TAG) {
-                a.parse_meta().ok().and_then(|meta| match meta {
-                    Meta::List(list) => {
-                        if list.path.is_ident(INFLUX_TAG) {
-                            list.nested.iter().find_map(|m| match m {
-                                syn::NestedMeta::Meta(Meta::Path(p)) => {
-                                    if p.is_ident("tag") {
-                                        Some(FieldType::Tag)
-                                    } else if p.is_ident("ignore") {
-                                        Some(FieldType::Ignore)
-                                    } else if p.is_ident("field") {
-                                        Some(FieldType::Field)
-                                    } else if p.is_ident("timestamp") {
-                                        Some(FieldType::Timestamp)
-                                    } else {
-                                        None
-                                    }
-                                }
-                                _ => None,
-                            })
-                        } else {
-                            None
-                        }
+        let mut field_type = None;
+        let mut rename = None;
+        let nested = value
+            .attrs
+            .iter()
+            .filter(|a| a.path.is_ident(INFLUX_TAG))
+            .filter_map(|a| match a.parse_meta() {
+                Ok(Meta::List(list)) => Some(list.nested),
+                _ => None,
+            })
+            .flatten();
+        for meta in nested {
+            match meta {
+                NestedMeta::Meta(Meta::Path(p)) => {
+                    if p.is_ident("tag") {
+                        field_type = Some(FieldType::Tag);
+                    } else if p.is_ident("ignore") {
+                        field_type = Some(FieldType::Ignore);
+                    } else if p.is_ident("field") {
+                        field_type = Some(FieldType::Field);
+                    } else if p.is_ident("timestamp") {
+                        field_type = Some(FieldType::Timestamp);
+                    } else if p.is_ident("measurement") {
+                        field_type = Some(FieldType::Measurement);
                     }
-                    _ => None,
-                })
-            } else {
-                None
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(lit_str),
+                    ..
+                })) if path.is_ident("rename") => {
+                    rename = Some(lit_str.value());
+                }
+                _ => {}
             }
-        });
-        Some(Self {
+        }
+
+        Self {
             field_type: field_type.unwrap_or(FieldType::Field),
             kind: value.ty,
-            ident: ident,
-        })
+            name: rename.unwrap_or_else(|| ident.to_string()),
+            ident,
+        }
     }
 }
 
-#[derive(Eq, PartialEq, Ord, PartialOrd, Debug)]
-enum FieldType {
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub(crate) enum FieldType {
     Tag,
     Field,
     Timestamp,
+    Measurement,
     Ignore,
 }
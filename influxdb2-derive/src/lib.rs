@@ -3,9 +3,11 @@
 #![recursion_limit = "128"]
 
 extern crate proc_macro;
+mod expand_model;
 mod expand_tuple;
 mod expand_writable;
 
+use expand_model::impl_model;
 use expand_tuple::{make_tuple_fields, make_tuple_tags};
 use expand_writable::impl_writeable;
 use itertools::izip;
@@ -238,6 +240,15 @@ pub fn impl_influx_writable(tokens: TokenStream) -> TokenStream {
     impl_writeable(tokens)
 }
 
+/// Implements `FromMap`, `WriteDataPoint` and `InfluxModel` from the
+/// annotations of `WriteDataPoint`, so that a struct is read and written
+/// the same way. Tags and fields are read from the columns of their keys,
+/// the timestamp from `_time` and the measurement from `_measurement`.
+#[proc_macro_derive(InfluxModel, attributes(measurement, influxdb))]
+pub fn impl_influx_model(tokens: TokenStream) -> TokenStream {
+    impl_model(tokens)
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -245,6 +256,7 @@ mod tests {
         let t = trybuild::TestCases::new();
         t.pass("tests/struct.rs");
         t.pass("tests/multistruct.rs");
-        t.pass("tests/writable.rs")
+        t.pass("tests/writable.rs");
+        t.pass("tests/model.rs");
     }
 }
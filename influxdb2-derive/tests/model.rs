@@ -0,0 +1,103 @@
+use influxdb2::model::{GenericMap, InfluxModel, Value};
+use influxdb2::models::WriteDataPoint;
+use influxdb2::FromMap;
+use influxdb2_derive::InfluxModel;
+
+#[derive(Debug, Default, PartialEq, InfluxModel)]
+#[measurement = "cpu"]
+struct Cpu {
+    #[influxdb(tag)]
+    host: String,
+    #[influxdb(tag, rename = "dc")]
+    region: Option<String>,
+    usage: f64,
+    #[influxdb(field, rename = "cores")]
+    count: Option<i64>,
+    #[influxdb(timestamp)]
+    time: i64,
+}
+
+#[derive(Debug, Default, PartialEq, InfluxModel)]
+struct Event {
+    #[influxdb(measurement)]
+    kind: String,
+    message: String,
+}
+
+#[derive(Debug, Default, PartialEq, InfluxModel)]
+#[measurement = "mem"]
+struct Mem {
+    used: u64,
+    #[influxdb(timestamp)]
+    time: u64,
+}
+
+fn main() {
+    let cpu = Cpu {
+        host: "server01".to_string(),
+        region: None,
+        usage: 0.5,
+        count: Some(4),
+        time: 1_000_000_000,
+    };
+
+    let mut writer = Vec::new();
+    cpu.write_data_point_to(&mut writer).unwrap();
+    assert_eq!(
+        &writer[..],
+        b"cpu,host=server01 usage=0.5,cores=4i 1000000000\n"
+    );
+
+    let mut map = GenericMap::new();
+    map.insert("_measurement".to_string(), Value::String("cpu".to_string()));
+    map.insert("host".to_string(), Value::String("server01".to_string()));
+    map.insert("usage".to_string(), Value::Double(0.5.into()));
+    map.insert("cores".to_string(), Value::Long(4));
+    map.insert(
+        "_time".to_string(),
+        Value::TimeRFC(chrono::DateTime::parse_from_rfc3339("1970-01-01T00:00:01Z").unwrap()),
+    );
+    assert_eq!(Cpu::from_genericmap(map), cpu);
+
+    assert_eq!(Cpu::MEASUREMENT, Some("cpu"));
+    assert_eq!(Cpu::TAGS, &["host", "dc"]);
+    assert_eq!(Cpu::FIELDS, &["usage", "cores"]);
+    let query = Cpu::query(
+        "b",
+        influxdb2::flux::now(),
+        influxdb2::flux::now(),
+        Some(Cpu::tag("dc").eq("eu")),
+    )
+    .to_string();
+    assert!(query.contains(r#"r._measurement == "cpu""#), "{}", query);
+    assert!(query.contains(r#"r.dc == "eu""#), "{}", query);
+    assert!(query.contains(r#"r._field == "cores""#), "{}", query);
+
+    let mut map = GenericMap::new();
+    map.insert(
+        "_measurement".to_string(),
+        Value::String("deploy".to_string()),
+    );
+    map.insert("message".to_string(), Value::String("done".to_string()));
+    let event = Event::from_genericmap(map);
+    assert_eq!(event.kind, "deploy");
+    assert_eq!(Event::MEASUREMENT, None);
+    let query = Event::query("b", influxdb2::flux::now(), influxdb2::flux::now(), None).to_string();
+    assert!(!query.contains("_measurement"), "{}", query);
+
+    let mem = Mem {
+        used: 1024,
+        time: 1_000_000_000,
+    };
+    let mut writer = Vec::new();
+    mem.write_data_point_to(&mut writer).unwrap();
+    assert_eq!(&writer[..], b"mem used=1024u 1000000000\n");
+
+    let mut map = GenericMap::new();
+    map.insert("used".to_string(), Value::UnsignedLong(1024));
+    map.insert(
+        "_time".to_string(),
+        Value::TimeRFC(chrono::DateTime::parse_from_rfc3339("1970-01-01T00:00:01Z").unwrap()),
+    );
+    assert_eq!(Mem::from_genericmap(map), mem);
+}
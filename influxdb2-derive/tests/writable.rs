@@ -28,6 +28,38 @@ struct Item2 {
     #[influxdb(timestamp)]
     time: u64,
 }
+#[derive(WriteDataPoint)]
+#[measurement = "some thing"]
+struct Item3 {
+    #[influxdb(tag)]
+    name: String,
+    field1: String,
+    #[influxdb(timestamp)]
+    time: u64,
+}
+
+#[derive(WriteDataPoint)]
+#[measurement = "cpu"]
+struct Item4 {
+    usage: f64,
+    #[influxdb(timestamp)]
+    time: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(WriteDataPoint)]
+struct Item5 {
+    #[influxdb(measurement)]
+    kind: String,
+    #[influxdb(tag, rename = "host name")]
+    host: &'static str,
+    #[influxdb(rename = "value")]
+    v: u64,
+    #[influxdb(ignore)]
+    _note: String,
+    #[influxdb(timestamp)]
+    time: std::time::SystemTime,
+}
+
 fn main() {
     use influxdb2::models::WriteDataPoint;
     use std::io::Write;
@@ -61,8 +93,60 @@ fn main() {
     item.write_data_point_to(&mut writer).unwrap();
     writer.flush().unwrap();
     println!("{}", std::str::from_utf8(&writer).unwrap());
+    assert_eq!(&writer[..], b"something,name=foo field2=33i 222222\n");
+
+    let item = Item3 {
+        name: "foo bar,baz=qux".to_string(),
+        field1: r#"say "hi" \o/"#.to_string(),
+        time: 222222u64,
+    };
+
+    let mut writer = Vec::new();
+    item.write_data_point_to(&mut writer).unwrap();
+    writer.flush().unwrap();
     assert_eq!(
-        &writer[..],
-        b"something,name=foo,name2=None field1=\"None\",field2=33i 222222\n"
-    )
+        std::str::from_utf8(&writer).unwrap(),
+        r#"some\ thing,name=foo\ bar\,baz\=qux field1="say \"hi\" \\o/" 222222
+"#
+    );
+
+    use chrono::TimeZone;
+
+    let item = Item4 {
+        usage: 0.5,
+        time: None,
+    };
+    let mut writer = Vec::new();
+    item.write_data_point_to(&mut writer).unwrap();
+    assert_eq!(&writer[..], b"cpu usage=0.5\n");
+
+    let item = Item4 {
+        usage: 0.5,
+        time: Some(chrono::Utc.timestamp(1, 0)),
+    };
+    let mut writer = Vec::new();
+    item.write_data_point_to(&mut writer).unwrap();
+    assert_eq!(&writer[..], b"cpu usage=0.5 1000000000\n");
+
+    let item = Item5 {
+        kind: "mem usage".to_string(),
+        host: "a",
+        v: 1,
+        _note: String::new(),
+        time: std::time::UNIX_EPOCH + std::time::Duration::from_nanos(7),
+    };
+    let mut writer = Vec::new();
+    item.write_data_point_to(&mut writer).unwrap();
+    assert_eq!(&writer[..], b"mem\\ usage,host\\ name=a value=1u 7\n");
+
+    let item = Item2 {
+        name: None,
+        name2: None,
+        field1: None,
+        field2: 1,
+        time: 1,
+    };
+    let mut writer = Vec::new();
+    item.write_data_point_to(&mut writer).unwrap();
+    assert_eq!(&writer[..], b"something field2=1i 1\n");
 }
@@ -0,0 +1,516 @@
+//! Annotations API
+//!
+//! Mark events, such as deploys, on graphs.
+
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::models::{AnnotationCreate, AnnotationEvent, AnnotationStream, StreamCreate};
+use crate::{Client, Http, RequestError, ReqwestProcessing};
+
+impl Client {
+    /// Create annotations in the organization specified by `org_id`.
+    pub async fn create_annotations(
+        &self,
+        org_id: &str,
+        annotations: &[AnnotationCreate],
+    ) -> Result<Vec<AnnotationEvent>, RequestError> {
+        let url = self.url("/api/v2/annotations");
+
+        let response = self
+            .request(Method::POST, &url)
+            .query(&[("orgID", org_id)])
+            .json(&annotations)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            let res = Http { status, text }.fail();
+            return res;
+        }
+
+        let res = response
+            .json::<Vec<AnnotationEvent>>()
+            .await
+            .context(ReqwestProcessing)?;
+        Ok(res)
+    }
+
+    /// List annotations matching specified parameters.
+    ///
+    /// Each annotation is deserialized into `T`, which can be
+    /// `AnnotationEvent` or any type that matches the shape of the
+    /// annotations you store, e.g. with typed stickers.
+    pub async fn list_annotations<T: DeserializeOwned>(
+        &self,
+        org_id: &str,
+        request: ListAnnotationsRequest,
+    ) -> Result<Vec<T>, RequestError> {
+        let url = self.url("/api/v2/annotations");
+
+        let response = self
+            .request(Method::GET, &url)
+            .query(&[("orgID", org_id)])
+            .query(&request)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            let res = Http { status, text }.fail();
+            return res;
+        }
+
+        let streams = response
+            .json::<Vec<AnnotationList>>()
+            .await
+            .context(ReqwestProcessing)?;
+
+        // The server groups annotations by stream; flatten them and put the
+        // stream name back into every annotation.
+        let mut res = vec![];
+        for list in streams {
+            for mut annotation in list.annotations {
+                annotation.insert(
+                    String::from("stream"),
+                    serde_json::Value::String(list.stream.clone()),
+                );
+                let item =
+                    serde_json::from_value(serde_json::Value::Object(annotation)).map_err(|e| {
+                        RequestError::Deserializing {
+                            text: e.to_string(),
+                        }
+                    })?;
+                res.push(item);
+            }
+        }
+        Ok(res)
+    }
+
+    /// Retrieve an annotation by ID
+    pub async fn find_annotation(
+        &self,
+        annotation_id: &str,
+    ) -> Result<AnnotationEvent, RequestError> {
+        let url = self.url(&format!("/api/v2/annotations/{}", annotation_id));
+
+        let response = self
+            .request(Method::GET, &url)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            let res = Http { status, text }.fail();
+            return res;
+        }
+
+        let res = response
+            .json::<AnnotationEvent>()
+            .await
+            .context(ReqwestProcessing)?;
+        Ok(res)
+    }
+
+    /// Update an annotation
+    pub async fn update_annotation(
+        &self,
+        annotation_id: &str,
+        annotation: &AnnotationCreate,
+    ) -> Result<AnnotationEvent, RequestError> {
+        let url = self.url(&format!("/api/v2/annotations/{}", annotation_id));
+
+        let response = self
+            .request(Method::PUT, &url)
+            .json(annotation)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            let res = Http { status, text }.fail();
+            return res;
+        }
+
+        let res = response
+            .json::<AnnotationEvent>()
+            .await
+            .context(ReqwestProcessing)?;
+        Ok(res)
+    }
+
+    /// Delete an annotation
+    pub async fn delete_annotation(&self, annotation_id: &str) -> Result<(), RequestError> {
+        let url = self.url(&format!("/api/v2/annotations/{}", annotation_id));
+
+        let response = self
+            .request(Method::DELETE, &url)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            Http { status, text }.fail()?;
+        }
+
+        Ok(())
+    }
+
+    /// List annotation streams, optionally only those whose name contains
+    /// `stream_includes`.
+    pub async fn list_annotation_streams(
+        &self,
+        org_id: &str,
+        stream_includes: Option<&str>,
+    ) -> Result<Vec<AnnotationStream>, RequestError> {
+        let url = self.url("/api/v2/streams");
+        let mut request = self.request(Method::GET, &url).query(&[("orgID", org_id)]);
+
+        if let Some(stream) = stream_includes {
+            request = request.query(&[("streamIncludes", stream)]);
+        }
+
+        let response = request.send().await.context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            let res = Http { status, text }.fail();
+            return res;
+        }
+
+        let res = response
+            .json::<Vec<AnnotationStream>>()
+            .await
+            .context(ReqwestProcessing)?;
+        Ok(res)
+    }
+
+    /// Create an annotation stream, or update the stream with the same name
+    /// if it already exists.
+    pub async fn create_annotation_stream(
+        &self,
+        org_id: &str,
+        stream: &StreamCreate,
+    ) -> Result<AnnotationStream, RequestError> {
+        let url = self.url("/api/v2/streams");
+
+        let response = self
+            .request(Method::PUT, &url)
+            .query(&[("orgID", org_id)])
+            .json(stream)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            let res = Http { status, text }.fail();
+            return res;
+        }
+
+        let res = response
+            .json::<AnnotationStream>()
+            .await
+            .context(ReqwestProcessing)?;
+        Ok(res)
+    }
+
+    /// Update an annotation stream by ID
+    pub async fn update_annotation_stream(
+        &self,
+        stream_id: &str,
+        stream: &StreamCreate,
+    ) -> Result<AnnotationStream, RequestError> {
+        let url = self.url(&format!("/api/v2/streams/{}", stream_id));
+
+        let response = self
+            .request(Method::PUT, &url)
+            .json(stream)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            let res = Http { status, text }.fail();
+            return res;
+        }
+
+        let res = response
+            .json::<AnnotationStream>()
+            .await
+            .context(ReqwestProcessing)?;
+        Ok(res)
+    }
+
+    /// Delete an annotation stream and all annotations in it
+    pub async fn delete_annotation_stream(&self, stream_id: &str) -> Result<(), RequestError> {
+        let url = self.url(&format!("/api/v2/streams/{}", stream_id));
+
+        let response = self
+            .request(Method::DELETE, &url)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            Http { status, text }.fail()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Request for list annotations API
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAnnotationsRequest {
+    /// Only returns annotations of this stream.
+    pub stream: Option<String>,
+    /// Only returns annotations ending after this time.
+    pub start_time: Option<DateTime<Utc>>,
+    /// Only returns annotations starting before this time.
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl ListAnnotationsRequest {
+    /// Returns an empty ListAnnotationsRequest
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Deserialize)]
+struct AnnotationList {
+    stream: String,
+    #[serde(default)]
+    annotations: Vec<serde_json::Map<String, serde_json::Value>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use mockito::{mock, Matcher};
+
+    const BASE_PATH: &str = "/api/v2/annotations";
+
+    #[tokio::test]
+    async fn create_annotations() {
+        let token = "some-token";
+        let org_id = "some-org_id";
+
+        let mut annotation = AnnotationCreate::new("deploy".to_string());
+        annotation.start_time = Some(Utc.ymd(2022, 1, 1).and_hms(0, 0, 0));
+
+        let mock_server = mock("POST", format!("{}?orgID={}", BASE_PATH, org_id).as_str())
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .match_body(r#"[{"summary":"deploy","startTime":"2022-01-01T00:00:00Z"}]"#)
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let _result = client.create_annotations(org_id, &[annotation]).await;
+
+        mock_server.assert();
+    }
+
+    #[tokio::test]
+    async fn list_annotations() {
+        let token = "some-token";
+        let org_id = "some-org_id";
+
+        let mock_server = mock("GET", BASE_PATH)
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("orgID".into(), org_id.into()),
+                Matcher::UrlEncoded("stream".into(), "ci".into()),
+                Matcher::UrlEncoded("startTime".into(), "2022-01-01T00:00:00Z".into()),
+            ]))
+            .with_body(
+                r#"[{"stream":"ci","annotations":[{
+                    "id":"a1",
+                    "summary":"deploy",
+                    "stickers":{"version":"1.2.3"},
+                    "startTime":"2022-01-01T10:00:00Z",
+                    "endTime":"2022-01-01T10:00:00Z"
+                }]}]"#,
+            )
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let request = ListAnnotationsRequest {
+            stream: Some("ci".to_string()),
+            start_time: Some(Utc.ymd(2022, 1, 1).and_hms(0, 0, 0)),
+            ..ListAnnotationsRequest::default()
+        };
+        let result = client
+            .list_annotations::<AnnotationEvent>(org_id, request)
+            .await
+            .unwrap();
+
+        mock_server.assert();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].stream, "ci");
+        assert_eq!(result[0].stickers["version"], "1.2.3");
+    }
+
+    #[tokio::test]
+    async fn list_annotations_custom_type() {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Deploy {
+            summary: String,
+            stickers: DeployStickers,
+            start_time: DateTime<Utc>,
+        }
+
+        #[derive(Deserialize)]
+        struct DeployStickers {
+            version: String,
+        }
+
+        let mock_server = mock("GET", BASE_PATH)
+            .match_query(Matcher::UrlEncoded("orgID".into(), "org_id".into()))
+            .with_body(
+                r#"[{"stream":"default","annotations":[{
+                    "id":"a1",
+                    "summary":"deploy",
+                    "stickers":{"version":"1.2.3"},
+                    "startTime":"2022-01-01T10:00:00Z",
+                    "endTime":"2022-01-01T10:00:00Z"
+                }]}]"#,
+            )
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", "");
+
+        let result = client
+            .list_annotations::<Deploy>("org_id", ListAnnotationsRequest::new())
+            .await
+            .unwrap();
+
+        mock_server.assert();
+        assert_eq!(result[0].summary, "deploy");
+        assert_eq!(result[0].stickers.version, "1.2.3");
+        assert_eq!(result[0].start_time, Utc.ymd(2022, 1, 1).and_hms(10, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn update_annotation() {
+        let token = "some-token";
+        let annotation_id = "some-annotation_id";
+
+        let mock_server = mock("PUT", format!("{}/{}", BASE_PATH, annotation_id).as_str())
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .match_body(r#"{"summary":"rollback"}"#)
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let _result = client
+            .update_annotation(
+                annotation_id,
+                &AnnotationCreate::new("rollback".to_string()),
+            )
+            .await;
+
+        mock_server.assert();
+    }
+
+    #[tokio::test]
+    async fn delete_annotation() {
+        let token = "some-token";
+        let annotation_id = "some-annotation_id";
+
+        let mock_server = mock(
+            "DELETE",
+            format!("{}/{}", BASE_PATH, annotation_id).as_str(),
+        )
+        .match_header("Authorization", format!("Token {}", token).as_str())
+        .with_status(204)
+        .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let result = client.delete_annotation(annotation_id).await;
+
+        mock_server.assert();
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn list_annotation_streams() {
+        let token = "some-token";
+        let org_id = "some-org_id";
+
+        let mock_server = mock(
+            "GET",
+            format!("/api/v2/streams?orgID={}&streamIncludes=ci", org_id).as_str(),
+        )
+        .match_header("Authorization", format!("Token {}", token).as_str())
+        .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let _result = client.list_annotation_streams(org_id, Some("ci")).await;
+
+        mock_server.assert();
+    }
+
+    #[tokio::test]
+    async fn create_annotation_stream() {
+        let token = "some-token";
+        let org_id = "some-org_id";
+
+        let mock_server = mock("PUT", format!("/api/v2/streams?orgID={}", org_id).as_str())
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .match_body(r#"{"stream":"ci"}"#)
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let _result = client
+            .create_annotation_stream(org_id, &StreamCreate::new("ci".to_string()))
+            .await;
+
+        mock_server.assert();
+    }
+
+    #[tokio::test]
+    async fn delete_annotation_stream() {
+        let token = "some-token";
+        let stream_id = "some-stream_id";
+
+        let mock_server = mock("DELETE", format!("/api/v2/streams/{}", stream_id).as_str())
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .with_status(204)
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let _result = client.delete_annotation_stream(stream_id).await;
+
+        mock_server.assert();
+    }
+}
@@ -0,0 +1,118 @@
+//! Backup API
+//!
+//! Stream metadata snapshots and TSM shard archives out of an InfluxDB
+//! instance.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::Method;
+use tokio::io::AsyncWrite;
+
+use crate::api::stream_response_to;
+use crate::{Client, RequestError};
+
+impl Client {
+    /// Stream a snapshot of the server metadata (KV store, SQL store and
+    /// bucket manifests) into `writer`.
+    ///
+    /// Returns the number of bytes written.
+    pub async fn backup_metadata<W>(&self, writer: &mut W) -> Result<u64, RequestError>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        let url = self.url("/api/v2/backup/metadata");
+        let request = self.request(Method::GET, &url);
+        stream_response_to(request, writer).await
+    }
+
+    /// Stream the TSM archive of a single shard into `writer`.
+    ///
+    /// If `since` is given, only files modified after that time are
+    /// included, which allows for incremental backups.
+    ///
+    /// Returns the number of bytes written.
+    pub async fn backup_shard<W>(
+        &self,
+        shard_id: u64,
+        since: Option<DateTime<Utc>>,
+        writer: &mut W,
+    ) -> Result<u64, RequestError>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        let url = self.url(&format!("/api/v2/backup/shards/{}", shard_id));
+        let mut request = self.request(Method::GET, &url);
+
+        if let Some(since) = since {
+            request = request.query(&[("since", since.to_rfc3339_opts(SecondsFormat::Secs, true))]);
+        }
+
+        stream_response_to(request, writer).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use mockito::mock;
+
+    #[tokio::test]
+    async fn backup_metadata() {
+        let token = "some-token";
+
+        let mock_server = mock("GET", "/api/v2/backup/metadata")
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .with_body("metadata-snapshot")
+            .create();
+
+        let client = Client::new(mockito::server_url(), "org", token);
+
+        let mut archive = Vec::new();
+        let written = client.backup_metadata(&mut archive).await.unwrap();
+
+        mock_server.assert();
+        assert_eq!(written, 17);
+        assert_eq!(archive, b"metadata-snapshot");
+    }
+
+    #[tokio::test]
+    async fn backup_shard_since() {
+        let token = "some-token";
+
+        let mock_server = mock(
+            "GET",
+            "/api/v2/backup/shards/42?since=2022-01-01T00%3A00%3A00Z",
+        )
+        .match_header("Authorization", format!("Token {}", token).as_str())
+        .with_body("tsm")
+        .create();
+
+        let client = Client::new(mockito::server_url(), "org", token);
+
+        let since = Utc.ymd(2022, 1, 1).and_hms(0, 0, 0);
+        let mut archive = Vec::new();
+        let written = client
+            .backup_shard(42, Some(since), &mut archive)
+            .await
+            .unwrap();
+
+        mock_server.assert();
+        assert_eq!(written, 3);
+    }
+
+    #[tokio::test]
+    async fn backup_shard_error() {
+        let mock_server = mock("GET", "/api/v2/backup/shards/1")
+            .with_status(404)
+            .create();
+
+        let client = Client::new(mockito::server_url(), "org", "");
+
+        let mut archive = Vec::new();
+        let result = client.backup_shard(1, None, &mut archive).await;
+
+        mock_server.assert();
+        assert!(result.is_err());
+        assert!(archive.is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+//! Config
+//!
+//! Get runtime configuration and feature flags of an InfluxDB instance
+
+use crate::models::{Config, Flags};
+use crate::{Client, Http, RequestError, ReqwestProcessing};
+use reqwest::{Method, StatusCode};
+use snafu::ResultExt;
+
+impl Client {
+    /// Get runtime configuration of an instance
+    pub async fn config(&self) -> Result<Config, RequestError> {
+        let config_url = self.url("/api/v2/config");
+        let response = self
+            .request(Method::GET, &config_url)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<Config>().await.context(ReqwestProcessing)?),
+            status => {
+                let text = response.text().await.context(ReqwestProcessing)?;
+                Http { status, text }.fail()?
+            }
+        }
+    }
+
+    /// Get feature flags of an instance
+    pub async fn flags(&self) -> Result<Flags, RequestError> {
+        let flags_url = self.url("/api/v2/flags");
+        let response = self
+            .request(Method::GET, &flags_url)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<Flags>().await.context(ReqwestProcessing)?),
+            status => {
+                let text = response.text().await.context(ReqwestProcessing)?;
+                Http { status, text }.fail()?
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::mock;
+
+    #[tokio::test]
+    async fn config() {
+        let token = "some-token";
+
+        let mock_server = mock("GET", "/api/v2/config")
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .with_body(r#"{"config":{"http-bind-address":":8086","query-concurrency":10}}"#)
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let result = client.config().await.unwrap();
+
+        mock_server.assert();
+        assert_eq!(result.config["query-concurrency"], 10);
+    }
+
+    #[tokio::test]
+    async fn flags() {
+        let token = "some-token";
+
+        let mock_server = mock("GET", "/api/v2/flags")
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .with_body(r#"{"someFlag":true}"#)
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let result = client.flags().await.unwrap();
+
+        mock_server.assert();
+        assert_eq!(result["someFlag"], true);
+    }
+}
@@ -1,6 +1,7 @@
 //! Labels
 
-use crate::models::{LabelCreateRequest, LabelResponse, LabelUpdate, LabelsResponse};
+use crate::models::resource::Type;
+use crate::models::{LabelCreateRequest, LabelMapping, LabelResponse, LabelUpdate, LabelsResponse};
 use crate::{Client, Http, RequestError, ReqwestProcessing, Serializing};
 use reqwest::{Method, StatusCode};
 use snafu::ResultExt;
@@ -132,6 +133,92 @@ impl Client {
             }
         }
     }
+
+    /// List all labels attached to a resource, e.g. a bucket or a task
+    pub async fn labels_by_resource(
+        &self,
+        resource_type: Type,
+        resource_id: &str,
+    ) -> Result<LabelsResponse, RequestError> {
+        let resource_labels_url = self.url(&format!(
+            "/api/v2/{}/{}/labels",
+            resource_type.as_str(),
+            resource_id
+        ));
+        let response = self
+            .request(Method::GET, &resource_labels_url)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+        match response.status() {
+            StatusCode::OK => Ok(response
+                .json::<LabelsResponse>()
+                .await
+                .context(ReqwestProcessing)?),
+            status => {
+                let text = response.text().await.context(ReqwestProcessing)?;
+                Http { status, text }.fail()?
+            }
+        }
+    }
+
+    /// Attach a label to a resource, e.g. a bucket or a task
+    pub async fn add_label(
+        &self,
+        resource_type: Type,
+        resource_id: &str,
+        label_id: &str,
+    ) -> Result<LabelResponse, RequestError> {
+        let resource_labels_url = self.url(&format!(
+            "/api/v2/{}/{}/labels",
+            resource_type.as_str(),
+            resource_id
+        ));
+        let body = LabelMapping::new(label_id.into());
+        let response = self
+            .request(Method::POST, &resource_labels_url)
+            .body(serde_json::to_string(&body).context(Serializing)?)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+        match response.status() {
+            StatusCode::CREATED => Ok(response
+                .json::<LabelResponse>()
+                .await
+                .context(ReqwestProcessing)?),
+            status => {
+                let text = response.text().await.context(ReqwestProcessing)?;
+                Http { status, text }.fail()?
+            }
+        }
+    }
+
+    /// Detach a label from a resource, e.g. a bucket or a task
+    pub async fn remove_label(
+        &self,
+        resource_type: Type,
+        resource_id: &str,
+        label_id: &str,
+    ) -> Result<(), RequestError> {
+        let resource_label_url = self.url(&format!(
+            "/api/v2/{}/{}/labels/{}",
+            resource_type.as_str(),
+            resource_id,
+            label_id
+        ));
+        let response = self
+            .request(Method::DELETE, &resource_label_url)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+        match response.status() {
+            StatusCode::NO_CONTENT => Ok(()),
+            status => {
+                let text = response.text().await.context(ReqwestProcessing)?;
+                Http { status, text }.fail()?
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -292,4 +379,69 @@ mod tests {
 
         mock_server.assert();
     }
+
+    #[tokio::test]
+    async fn labels_by_resource() {
+        let token = "some-token";
+        let bucket_id = "some-bucket_id";
+
+        let mock_server = mock(
+            "GET",
+            format!("/api/v2/buckets/{}/labels", bucket_id).as_str(),
+        )
+        .match_header("Authorization", format!("Token {}", token).as_str())
+        .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let _result = client.labels_by_resource(Type::Buckets, bucket_id).await;
+
+        mock_server.assert();
+    }
+
+    #[tokio::test]
+    async fn add_label() {
+        let token = "some-token";
+        let task_id = "some-task_id";
+        let label_id = "some-label_id";
+
+        let mock_server = mock("POST", format!("/api/v2/tasks/{}/labels", task_id).as_str())
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .match_body(format!(r#"{{"labelID":"{}"}}"#, label_id).as_str())
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let _result = client.add_label(Type::Tasks, task_id, label_id).await;
+
+        mock_server.assert();
+    }
+
+    #[tokio::test]
+    async fn remove_label() {
+        let token = "some-token";
+        let endpoint_id = "some-endpoint_id";
+        let label_id = "some-label_id";
+
+        let mock_server = mock(
+            "DELETE",
+            format!(
+                "/api/v2/notificationEndpoints/{}/labels/{}",
+                endpoint_id, label_id
+            )
+            .as_str(),
+        )
+        .match_header("Authorization", format!("Token {}", token).as_str())
+        .with_status(204)
+        .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let result = client
+            .remove_label(Type::NotificationEndpoints, endpoint_id, label_id)
+            .await;
+
+        mock_server.assert();
+        assert!(result.is_ok());
+    }
 }
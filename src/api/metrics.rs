@@ -0,0 +1,404 @@
+//! Metrics
+//!
+//! Get internal metrics of an InfluxDB instance in the Prometheus text
+//! exposition format
+
+use std::collections::BTreeMap;
+
+use crate::models::metrics::{
+    Histogram, HistogramBucket, Metric, MetricFamily, MetricType, MetricValue, Metrics, Quantile,
+    Summary,
+};
+use crate::{Client, Http, RequestError, ReqwestProcessing};
+use reqwest::{Method, StatusCode};
+use snafu::ResultExt;
+
+impl Client {
+    /// Get internal metrics of an instance
+    pub async fn metrics(&self) -> Result<Metrics, RequestError> {
+        let metrics_url = self.url("/metrics");
+        let response = self
+            .request(Method::GET, &metrics_url)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        match response.status() {
+            StatusCode::OK => {
+                let text = response.text().await.context(ReqwestProcessing)?;
+                parse_metrics(&text)
+            }
+            status => {
+                let text = response.text().await.context(ReqwestProcessing)?;
+                Http { status, text }.fail()?
+            }
+        }
+    }
+}
+
+struct Sample {
+    name: String,
+    labels: BTreeMap<String, String>,
+    value: f64,
+    timestamp: Option<i64>,
+}
+
+fn parse_metrics(text: &str) -> Result<Metrics, RequestError> {
+    let mut metrics = Metrics::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut parts = comment.trim_start().splitn(3, ' ');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("HELP"), Some(name), help) => {
+                    let family = family_mut(&mut metrics, name, MetricType::Untyped);
+                    family.help = unescape(help.unwrap_or(""));
+                }
+                (Some("TYPE"), Some(name), Some(t)) => {
+                    let t = parse_metric_type(t.trim())?;
+                    family_mut(&mut metrics, name, t).r#type = t;
+                }
+                // Any other comment is ignored
+                _ => {}
+            }
+            continue;
+        }
+
+        let sample = parse_sample(line)?;
+        add_sample(&mut metrics, sample)?;
+    }
+
+    Ok(metrics)
+}
+
+fn parse_metric_type(s: &str) -> Result<MetricType, RequestError> {
+    match s {
+        "counter" => Ok(MetricType::Counter),
+        "gauge" => Ok(MetricType::Gauge),
+        "histogram" => Ok(MetricType::Histogram),
+        "summary" => Ok(MetricType::Summary),
+        "untyped" => Ok(MetricType::Untyped),
+        _ => Err(RequestError::Deserializing {
+            text: format!("unknown metric type: {}", s),
+        }),
+    }
+}
+
+fn family_mut<'a>(metrics: &'a mut Metrics, name: &str, t: MetricType) -> &'a mut MetricFamily {
+    match metrics.families.iter().position(|f| f.name == name) {
+        Some(i) => &mut metrics.families[i],
+        None => {
+            metrics
+                .families
+                .push(MetricFamily::new(name.to_string(), t));
+            metrics.families.last_mut().unwrap()
+        }
+    }
+}
+
+fn add_sample(metrics: &mut Metrics, sample: Sample) -> Result<(), RequestError> {
+    let Sample {
+        name,
+        mut labels,
+        value,
+        timestamp,
+    } = sample;
+
+    // Histogram and summary samples are exposed as several series with
+    // suffixed names; find the family they belong to.
+    let mut suffix = "";
+    let mut family = metrics.families.iter().position(|f| f.name == name);
+    if family.is_none() {
+        for s in &["_bucket", "_sum", "_count"] {
+            if let Some(base) = name.strip_suffix(s) {
+                family = metrics.families.iter().position(|f| {
+                    f.name == base
+                        && matches!(f.r#type, MetricType::Histogram | MetricType::Summary)
+                });
+                if family.is_some() {
+                    suffix = s;
+                    break;
+                }
+            }
+        }
+    }
+    let family = match family {
+        Some(i) => &mut metrics.families[i],
+        None => family_mut(metrics, &name, MetricType::Untyped),
+    };
+
+    match family.r#type {
+        MetricType::Counter | MetricType::Gauge | MetricType::Untyped => {
+            let value = match family.r#type {
+                MetricType::Counter => MetricValue::Counter(value),
+                MetricType::Gauge => MetricValue::Gauge(value),
+                _ => MetricValue::Untyped(value),
+            };
+            family.metrics.push(Metric {
+                labels,
+                value,
+                timestamp,
+            });
+        }
+        MetricType::Histogram => {
+            let le = labels.remove("le");
+            let metric = series_mut(family, labels, timestamp, || {
+                MetricValue::Histogram(Histogram::default())
+            });
+            if let MetricValue::Histogram(h) = &mut metric.value {
+                match suffix {
+                    "_bucket" => {
+                        let le = le.ok_or_else(|| RequestError::Deserializing {
+                            text: format!("histogram bucket without le label: {}", name),
+                        })?;
+                        h.buckets.push(HistogramBucket {
+                            upper_bound: parse_float(&le)?,
+                            cumulative_count: value as u64,
+                        });
+                    }
+                    "_sum" => h.sum = value,
+                    "_count" => h.count = value as u64,
+                    _ => {
+                        return Err(RequestError::Deserializing {
+                            text: format!("unexpected histogram sample: {}", name),
+                        })
+                    }
+                }
+            }
+        }
+        MetricType::Summary => {
+            let quantile = labels.remove("quantile");
+            let metric = series_mut(family, labels, timestamp, || {
+                MetricValue::Summary(Summary::default())
+            });
+            if let MetricValue::Summary(s) = &mut metric.value {
+                match (suffix, quantile) {
+                    ("", Some(q)) => s.quantiles.push(Quantile {
+                        quantile: parse_float(&q)?,
+                        value,
+                    }),
+                    ("_sum", _) => s.sum = value,
+                    ("_count", _) => s.count = value as u64,
+                    _ => {
+                        return Err(RequestError::Deserializing {
+                            text: format!("unexpected summary sample: {}", name),
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn series_mut(
+    family: &mut MetricFamily,
+    labels: BTreeMap<String, String>,
+    timestamp: Option<i64>,
+    init: impl FnOnce() -> MetricValue,
+) -> &mut Metric {
+    match family.metrics.iter().position(|m| m.labels == labels) {
+        Some(i) => &mut family.metrics[i],
+        None => {
+            family.metrics.push(Metric {
+                labels,
+                value: init(),
+                timestamp,
+            });
+            family.metrics.last_mut().unwrap()
+        }
+    }
+}
+
+fn parse_sample(line: &str) -> Result<Sample, RequestError> {
+    let name_end = line
+        .find(|c: char| c == '{' || c.is_whitespace())
+        .ok_or_else(|| RequestError::Deserializing {
+            text: format!("invalid metric line: {}", line),
+        })?;
+    let name = line[..name_end].to_string();
+    let mut rest = &line[name_end..];
+
+    let mut labels = BTreeMap::new();
+    if let Some(r) = rest.strip_prefix('{') {
+        rest = parse_labels(r, &mut labels).ok_or_else(|| RequestError::Deserializing {
+            text: format!("invalid metric labels: {}", line),
+        })?;
+    }
+
+    let mut parts = rest.split_whitespace();
+    let value = parse_float(parts.next().unwrap_or(""))?;
+    let timestamp = match parts.next() {
+        Some(ts) => Some(ts.parse::<i64>().map_err(|e| RequestError::Deserializing {
+            text: e.to_string(),
+        })?),
+        None => None,
+    };
+
+    Ok(Sample {
+        name,
+        labels,
+        value,
+        timestamp,
+    })
+}
+
+/// Parses `key="value",...}` and returns the remainder of the line after the
+/// closing brace.
+fn parse_labels<'a>(mut s: &'a str, labels: &mut BTreeMap<String, String>) -> Option<&'a str> {
+    loop {
+        s = s.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if let Some(rest) = s.strip_prefix('}') {
+            return Some(rest);
+        }
+
+        let eq = s.find('=')?;
+        let key = s[..eq].trim().to_string();
+        s = s[eq + 1..].trim_start().strip_prefix('"')?;
+
+        let mut value = String::new();
+        let mut chars = s.char_indices();
+        let end = loop {
+            match chars.next()? {
+                (i, '"') => break i,
+                (_, '\\') => match chars.next()? {
+                    (_, 'n') => value.push('\n'),
+                    (_, c) => value.push(c),
+                },
+                (_, c) => value.push(c),
+            }
+        };
+        labels.insert(key, value);
+        s = &s[end + 1..];
+    }
+}
+
+fn parse_float(s: &str) -> Result<f64, RequestError> {
+    s.parse::<f64>().map_err(|e| RequestError::Deserializing {
+        text: format!("invalid metric value {:?}: {}", s, e),
+    })
+}
+
+fn unescape(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => res.push('\n'),
+                Some(c) => res.push(c),
+                None => res.push('\\'),
+            },
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::mock;
+
+    const EXPOSITION: &str = r#"# HELP go_goroutines Number of goroutines that currently exist.
+# TYPE go_goroutines gauge
+go_goroutines 42
+# HELP http_api_requests_total Number of http requests received
+# TYPE http_api_requests_total counter
+http_api_requests_total{handler="platform",method="GET",path="/api/v2/query",status="2XX"} 10
+http_api_requests_total{handler="platform",method="POST",path="/api/v2/write",status="2XX"} 1027 1395066363000
+# HELP http_api_request_duration_seconds Time taken to respond to HTTP request
+# TYPE http_api_request_duration_seconds histogram
+http_api_request_duration_seconds_bucket{path="/api/v2/write",le="0.005"} 4
+http_api_request_duration_seconds_bucket{path="/api/v2/write",le="0.01"} 9
+http_api_request_duration_seconds_bucket{path="/api/v2/write",le="+Inf"} 10
+http_api_request_duration_seconds_sum{path="/api/v2/write"} 0.06
+http_api_request_duration_seconds_count{path="/api/v2/write"} 10
+# TYPE go_gc_duration_seconds summary
+go_gc_duration_seconds{quantile="0.5"} 0.0001
+go_gc_duration_seconds{quantile="1"} 0.002
+go_gc_duration_seconds_sum 0.5
+go_gc_duration_seconds_count 100
+storage_label_escape{msg="a \"quoted\", value"} NaN
+"#;
+
+    #[test]
+    fn parse_exposition() {
+        let metrics = parse_metrics(EXPOSITION).unwrap();
+        assert_eq!(metrics.families.len(), 5);
+
+        let goroutines = metrics.family("go_goroutines").unwrap();
+        assert_eq!(goroutines.r#type, MetricType::Gauge);
+        assert_eq!(
+            goroutines.help,
+            "Number of goroutines that currently exist."
+        );
+        assert_eq!(goroutines.metrics[0].value, MetricValue::Gauge(42.0));
+
+        let requests = metrics.family("http_api_requests_total").unwrap();
+        assert_eq!(requests.metrics.len(), 2);
+        assert_eq!(requests.metrics[1].labels["method"], "POST");
+        assert_eq!(requests.metrics[1].value, MetricValue::Counter(1027.0));
+        assert_eq!(requests.metrics[1].timestamp, Some(1395066363000));
+
+        let duration = metrics.family("http_api_request_duration_seconds").unwrap();
+        assert_eq!(duration.metrics.len(), 1);
+        assert_eq!(duration.metrics[0].labels.len(), 1);
+        match &duration.metrics[0].value {
+            MetricValue::Histogram(h) => {
+                assert_eq!(h.buckets.len(), 3);
+                assert_eq!(h.buckets[2].upper_bound, f64::INFINITY);
+                assert_eq!(h.buckets[2].cumulative_count, 10);
+                assert_eq!(h.sum, 0.06);
+                assert_eq!(h.count, 10);
+            }
+            v => panic!("expected histogram, got {:?}", v),
+        }
+
+        let gc = metrics.family("go_gc_duration_seconds").unwrap();
+        match &gc.metrics[0].value {
+            MetricValue::Summary(s) => {
+                assert_eq!(s.quantiles[1].quantile, 1.0);
+                assert_eq!(s.quantiles[1].value, 0.002);
+                assert_eq!(s.count, 100);
+            }
+            v => panic!("expected summary, got {:?}", v),
+        }
+
+        let untyped = metrics.family("storage_label_escape").unwrap();
+        assert_eq!(untyped.r#type, MetricType::Untyped);
+        assert_eq!(untyped.metrics[0].labels["msg"], r#"a "quoted", value"#);
+        match untyped.metrics[0].value {
+            MetricValue::Untyped(v) => assert!(v.is_nan()),
+            ref v => panic!("expected untyped, got {:?}", v),
+        }
+    }
+
+    #[test]
+    fn parse_invalid_value() {
+        assert!(parse_metrics("some_metric{a=\"b\"} abc").is_err());
+    }
+
+    #[tokio::test]
+    async fn metrics() {
+        let mock_server = mock("GET", "/metrics")
+            .with_body("go_goroutines 42\n")
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", "");
+
+        let result = client.metrics().await.unwrap();
+
+        mock_server.assert();
+        assert_eq!(
+            result.family("go_goroutines").unwrap().metrics[0].value,
+            MetricValue::Untyped(42.0)
+        );
+    }
+}
@@ -1,12 +1,55 @@
 //! InfluxDB v2.0 Client API
+pub mod annotations;
 pub mod authorizations;
+pub mod backup;
 pub mod buckets;
+pub mod config;
 pub mod delete;
 pub mod health;
 pub mod label;
+pub mod metrics;
+pub mod notebooks;
 pub mod organization;
 pub mod query;
 pub mod ready;
+pub mod restore;
+pub mod schema;
 pub mod setup;
 pub mod task;
 pub mod write;
+
+use futures::StreamExt;
+use reqwest::RequestBuilder;
+use snafu::ResultExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::{Http, Io, RequestError, ReqwestProcessing};
+
+/// Send `request` and stream the response body into `writer`, returning the
+/// number of bytes written
+pub(crate) async fn stream_response_to<W>(
+    request: RequestBuilder,
+    writer: &mut W,
+) -> Result<u64, RequestError>
+where
+    W: AsyncWrite + Unpin + Send + ?Sized,
+{
+    let response = request.send().await.context(ReqwestProcessing)?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.context(ReqwestProcessing)?;
+        return Http { status, text }.fail();
+    }
+
+    let mut written = 0;
+    let mut body = response.bytes_stream();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.context(ReqwestProcessing)?;
+        writer.write_all(&chunk).await.context(Io)?;
+        written += chunk.len() as u64;
+    }
+    writer.flush().await.context(Io)?;
+
+    Ok(written)
+}
@@ -0,0 +1,268 @@
+//! Notebooks API
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::models::{Notebook, NotebookParams, Notebooks};
+use crate::{Client, Http, RequestError, ReqwestProcessing};
+
+impl Client {
+    /// List all notebooks of an organization.
+    pub async fn list_notebooks(
+        &self,
+        request: ListNotebooksRequest,
+    ) -> Result<Notebooks, RequestError> {
+        let url = self.url("/api/v2/notebooks");
+
+        let response = self
+            .request(Method::GET, &url)
+            .query(&request)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            let res = Http { status, text }.fail();
+            return res;
+        }
+
+        let res = response
+            .json::<Notebooks>()
+            .await
+            .context(ReqwestProcessing)?;
+        Ok(res)
+    }
+
+    /// Retrieve a notebook by ID.
+    pub async fn find_notebook(&self, notebook_id: &str) -> Result<Notebook, RequestError> {
+        let url = self.url(&format!("/api/v2/notebooks/{}", notebook_id));
+
+        let response = self
+            .request(Method::GET, &url)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            let res = Http { status, text }.fail();
+            return res;
+        }
+
+        let res = response
+            .json::<Notebook>()
+            .await
+            .context(ReqwestProcessing)?;
+        Ok(res)
+    }
+
+    /// Create a new notebook.
+    pub async fn create_notebook(&self, params: NotebookParams) -> Result<Notebook, RequestError> {
+        let url = self.url("/api/v2/notebooks");
+
+        let response = self
+            .request(Method::POST, &url)
+            .json(&params)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            let res = Http { status, text }.fail();
+            return res;
+        }
+
+        let res = response
+            .json::<Notebook>()
+            .await
+            .context(ReqwestProcessing)?;
+        Ok(res)
+    }
+
+    /// Update a notebook specified by notebook id.
+    pub async fn update_notebook(
+        &self,
+        notebook_id: &str,
+        params: NotebookParams,
+    ) -> Result<Notebook, RequestError> {
+        let url = self.url(&format!("/api/v2/notebooks/{}", notebook_id));
+
+        let response = self
+            .request(Method::PATCH, &url)
+            .json(&params)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            let res = Http { status, text }.fail();
+            return res;
+        }
+
+        let res = response
+            .json::<Notebook>()
+            .await
+            .context(ReqwestProcessing)?;
+        Ok(res)
+    }
+
+    /// Delete a notebook specified by notebook id.
+    pub async fn delete_notebook(&self, notebook_id: &str) -> Result<(), RequestError> {
+        let url = self.url(&format!("/api/v2/notebooks/{}", notebook_id));
+
+        let response = self
+            .request(Method::DELETE, &url)
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            Http { status, text }.fail()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Request for list notebooks API
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListNotebooksRequest {
+    /// The organization ID.
+    #[serde(rename = "orgID")]
+    pub org_id: String,
+    /// Number of notebooks to return.
+    pub limit: Option<u64>,
+    /// Offset from which to return notebooks.
+    pub offset: Option<u64>,
+}
+
+impl ListNotebooksRequest {
+    /// Returns instance of ListNotebooksRequest
+    pub fn new(org_id: String) -> Self {
+        Self {
+            org_id,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::mock;
+
+    const BASE_PATH: &str = "/api/v2/notebooks";
+
+    #[tokio::test]
+    async fn list_notebooks() {
+        let token = "some-token";
+        let org_id = "some-org_id";
+
+        let mock_server = mock("GET", format!("{}?orgID={}", BASE_PATH, org_id).as_str())
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .with_body(
+                r#"{"flows":[{"id":"n1","orgID":"some-org_id","name":"cpu","spec":{"pipes":[]}}]}"#,
+            )
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let result = client
+            .list_notebooks(ListNotebooksRequest::new(org_id.to_string()))
+            .await
+            .unwrap();
+
+        mock_server.assert();
+        assert_eq!(result.notebooks.len(), 1);
+        assert_eq!(result.notebooks[0].spec, serde_json::json!({"pipes": []}));
+    }
+
+    #[tokio::test]
+    async fn find_notebook() {
+        let token = "some-token";
+        let notebook_id = "some-notebook_id";
+
+        let mock_server = mock("GET", format!("{}/{}", BASE_PATH, notebook_id).as_str())
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let _result = client.find_notebook(notebook_id).await;
+
+        mock_server.assert();
+    }
+
+    #[tokio::test]
+    async fn create_notebook_from_export() {
+        let token = "some-token";
+        let notebook = Notebook {
+            id: Some("n1".to_string()),
+            org_id: Some("source-org".to_string()),
+            name: "cpu".to_string(),
+            spec: serde_json::json!({"pipes": []}),
+            ..Notebook::default()
+        };
+
+        let mock_server = mock("POST", BASE_PATH)
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .match_body(r#"{"orgID":"target-org","name":"cpu","spec":{"pipes":[]}}"#)
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let _result = client
+            .create_notebook(notebook.to_params("target-org".to_string()))
+            .await;
+
+        mock_server.assert();
+    }
+
+    #[tokio::test]
+    async fn update_notebook() {
+        let token = "some-token";
+        let notebook_id = "some-notebook_id";
+
+        let mock_server = mock("PATCH", format!("{}/{}", BASE_PATH, notebook_id).as_str())
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .match_body(r#"{"orgID":"org","name":"cpu","spec":{}}"#)
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let params =
+            NotebookParams::new("org".to_string(), "cpu".to_string(), serde_json::json!({}));
+        let _result = client.update_notebook(notebook_id, params).await;
+
+        mock_server.assert();
+    }
+
+    #[tokio::test]
+    async fn delete_notebook() {
+        let token = "some-token";
+        let notebook_id = "some-notebook_id";
+
+        let mock_server = mock("DELETE", format!("{}/{}", BASE_PATH, notebook_id).as_str())
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .with_status(204)
+            .create();
+
+        let client = Client::new(mockito::server_url(), "", token);
+
+        let result = client.delete_notebook(notebook_id).await;
+
+        mock_server.assert();
+        assert!(result.is_ok());
+    }
+}
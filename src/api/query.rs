@@ -2,22 +2,31 @@
 //!
 //! Query InfluxDB using InfluxQL or Flux Query
 
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::pin::Pin;
 use std::str::FromStr;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
-use crate::{Client, Http, RequestError, ReqwestProcessing, Serializing};
+use crate::api::stream_response_to;
+use crate::{Client, Http, RequestError, RequestOptions, ReqwestProcessing, Serializing};
 
 use base64::decode;
-use chrono::DateTime;
+use bytes::Bytes;
+use chrono::{DateTime, FixedOffset};
 use csv::StringRecord;
 use fallible_iterator::FallibleIterator;
+use futures::Stream;
 use go_parse_duration::parse_duration;
 use influxdb2_structmap::value::Value;
 use influxdb2_structmap::{FromMap, GenericMap};
 use ordered_float::OrderedFloat;
-use reqwest::{Method, StatusCode};
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
 use snafu::ResultExt;
+use tokio::io::AsyncWrite;
 
+use crate::models::ast::dialect::Annotations;
+use crate::models::ast::Dialect;
 use crate::models::{
     AnalyzeQueryResponse, AstResponse, FluxSuggestion, FluxSuggestions, LanguageRequest, Query,
 };
@@ -26,21 +35,146 @@ use crate::models::{
 #[derive(Clone, Debug)]
 pub struct QueryTableIter {
     text: String,
+    dialect: Dialect,
 }
 
 impl<'a> QueryTableIter {
-    fn new(text: String) -> Self {
-        Self { text }
+    fn new(text: String, dialect: Dialect) -> Self {
+        Self { text, dialect }
     }
 
     /// Get the iterator
     pub fn result(&'a self) -> impl FallibleIterator<Item = FluxRecord, Error = RequestError> + 'a {
-        QueryTableResult::new(&self.text)
+        QueryTableResult::with_dialect(&self.text, &self.dialect)
     }
 
     /// Is the response empty?
     pub fn is_empty(&self) -> bool {
-        matches!(QueryTableResult::new(&self.text).next(), Ok(None))
+        matches!(
+            QueryTableResult::with_dialect(&self.text, &self.dialect).next(),
+            Ok(None)
+        )
+    }
+}
+
+type BodyStream = Pin<Box<dyn Stream<Item = reqwest::Result<Bytes>> + Send>>;
+
+/// Stream of records parsed as the query response arrives
+pub struct QueryStream {
+    body: BodyStream,
+    buffer: Vec<u8>,
+    scanned: usize,
+    in_quotes: bool,
+    /// Whether the rows parsed so far ended with an empty line, which
+    /// starts a new table on the next row
+    after_empty_line: bool,
+    dialect: Dialect,
+    parser: FluxCsvParser,
+    records: VecDeque<FluxRecord>,
+    error: Option<RequestError>,
+    done: bool,
+}
+
+impl std::fmt::Debug for QueryStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueryStream")
+            .field("buffered", &self.buffer.len())
+            .field("records", &self.records.len())
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl QueryStream {
+    fn new(body: BodyStream, dialect: Dialect) -> Self {
+        Self {
+            body,
+            buffer: Vec::new(),
+            scanned: 0,
+            in_quotes: false,
+            after_empty_line: false,
+            parser: FluxCsvParser::new(&dialect),
+            dialect,
+            records: VecDeque::new(),
+            error: None,
+            done: false,
+        }
+    }
+
+    /// Length of the buffered rows that are complete, i.e. end with a line
+    /// break outside of a quoted value
+    fn complete_rows(&mut self) -> usize {
+        let mut end = 0;
+        for (i, c) in self.buffer.iter().enumerate().skip(self.scanned) {
+            match c {
+                b'"' => self.in_quotes = !self.in_quotes,
+                b'\n' if !self.in_quotes => end = i + 1,
+                _ => {}
+            }
+        }
+        self.scanned = self.buffer.len();
+        end
+    }
+
+    fn parse_rows(&mut self, end: usize) -> Result<(), RequestError> {
+        let text: Vec<u8> = self.buffer.drain(..end).collect();
+        self.scanned -= end;
+
+        let mut reader = csv_reader(&self.dialect, &text[..]);
+        let mut row = StringRecord::new();
+        while read_row(&mut reader, &mut row)? {
+            let after_empty_line =
+                std::mem::take(&mut self.after_empty_line) || after_empty_line(&text, &row);
+            if let Some(record) = self.parser.parse_row(&row, after_empty_line)? {
+                self.records.push_back(record);
+            }
+        }
+
+        // The rows are drained up to a line break, so the last line is empty
+        // when nothing but a line break follows the one before it
+        let last_line = text.strip_suffix(b"\n").unwrap_or(&text);
+        let last_line = last_line.strip_suffix(b"\r").unwrap_or(last_line);
+        self.after_empty_line = last_line.is_empty() || last_line.ends_with(b"\n");
+        Ok(())
+    }
+}
+
+impl Stream for QueryStream {
+    type Item = Result<FluxRecord, RequestError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(record) = this.records.pop_front() {
+                return Poll::Ready(Some(Ok(record)));
+            }
+            if this.done {
+                return Poll::Ready(this.error.take().map(Err));
+            }
+
+            let end = match this.body.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    this.buffer.extend_from_slice(&chunk);
+                    this.complete_rows()
+                }
+                Poll::Ready(Some(Err(source))) => {
+                    this.done = true;
+                    this.error = Some(RequestError::ReqwestProcessing { source });
+                    continue;
+                }
+                Poll::Ready(None) => {
+                    this.done = true;
+                    this.buffer.len()
+                }
+                Poll::Pending => return Poll::Pending,
+            };
+            if end > 0 {
+                if let Err(e) = this.parse_rows(end) {
+                    this.done = true;
+                    this.error = Some(e);
+                }
+            }
+        }
     }
 }
 
@@ -93,67 +227,97 @@ impl Client {
 
     /// Query
     pub async fn query<T: FromMap>(&self, query: Option<Query>) -> Result<Vec<T>, RequestError> {
-        let req_url = self.url("/api/v2/query");
-        let body = serde_json::to_string(&query.unwrap_or_default()).context(Serializing)?;
-
-        let response = self
-            .request(Method::POST, &req_url)
-            .header("Accepting-Encoding", "identity")
-            .header("Content-Type", "application/json")
-            .query(&[("org", &self.org)])
-            .body(body)
-            .send()
+        self.query_with_options(query, &RequestOptions::default())
             .await
-            .context(ReqwestProcessing)?;
+    }
 
-        match response.status() {
-            StatusCode::OK => {
-                let text = response.text().await.unwrap();
-                let qtr = QueryTableResult::new(&text[..]);
-                let qr = QueryResult::new(qtr)?;
-                let mut res = vec![];
-                for item in qr.items {
-                    res.push(T::from_genericmap(item));
-                }
-                Ok(res)
-            }
-            status => {
-                let text = response.text().await.context(ReqwestProcessing)?;
-                Http { status, text }.fail()?
-            }
+    /// Query with per-request options
+    pub async fn query_with_options<T: FromMap>(
+        &self,
+        query: Option<Query>,
+        options: &RequestOptions,
+    ) -> Result<Vec<T>, RequestError> {
+        let (response, dialect) = self.send_query(query, options).await?;
+        let text = response.text().await.context(ReqwestProcessing)?;
+        let qtr = QueryTableResult::with_dialect(&text[..], &dialect);
+        let qr = QueryResult::new(qtr)?;
+        let mut res = vec![];
+        for item in qr.items {
+            res.push(T::from_genericmap(item));
         }
+        Ok(res)
     }
 
     /// Query Raw
     pub async fn query_raw(&self, query: Option<Query>) -> Result<Vec<FluxRecord>, RequestError> {
-        let req_url = self.url("/api/v2/query");
-        let body = serde_json::to_string(&query.unwrap_or_default()).context(Serializing)?;
-
-        let response = self
-            .request(Method::POST, &req_url)
-            .header("Accepting-Encoding", "identity")
-            .header("Content-Type", "application/json")
-            .query(&[("org", &self.org)])
-            .body(body)
-            .send()
+        self.query_raw_with_options(query, &RequestOptions::default())
             .await
-            .context(ReqwestProcessing)?;
+    }
 
-        match response.status() {
-            StatusCode::OK => {
-                let text = response.text().await.unwrap();
-                let qtr = QueryTableResult::new(&text[..]);
-                let mut records = vec![];
-                for record in qtr.iterator() {
-                    records.push(record?);
-                }
-                Ok(records)
-            }
-            status => {
-                let text = response.text().await.context(ReqwestProcessing)?;
-                Http { status, text }.fail()?
-            }
+    /// Query Raw with per-request options
+    pub async fn query_raw_with_options(
+        &self,
+        query: Option<Query>,
+        options: &RequestOptions,
+    ) -> Result<Vec<FluxRecord>, RequestError> {
+        let (response, dialect) = self.send_query(query, options).await?;
+        let text = response.text().await.context(ReqwestProcessing)?;
+        let qtr = QueryTableResult::with_dialect(&text[..], &dialect);
+        let mut records = vec![];
+        for record in qtr.iterator() {
+            records.push(record?);
         }
+        Ok(records)
+    }
+
+    /// Query with profilers enabled by `Query::with_profilers`, returning
+    /// the profiler results apart from the data records
+    pub async fn query_profiled(
+        &self,
+        query: Option<Query>,
+    ) -> Result<ProfiledQueryResult, RequestError> {
+        let records = self.query_raw(query).await?;
+        Ok(ProfiledQueryResult::new(records))
+    }
+
+    /// Query returning the results grouped into tables, with column
+    /// metadata. The `table` of each record is the index of its table in the
+    /// returned list.
+    pub async fn query_tables(&self, query: Option<Query>) -> Result<Vec<FluxTable>, RequestError> {
+        self.query_tables_with_options(query, &RequestOptions::default())
+            .await
+    }
+
+    /// Query returning tables with per-request options
+    pub async fn query_tables_with_options(
+        &self,
+        query: Option<Query>,
+        options: &RequestOptions,
+    ) -> Result<Vec<FluxTable>, RequestError> {
+        let (response, dialect) = self.send_query(query, options).await?;
+        let text = response.text().await.context(ReqwestProcessing)?;
+        QueryTableResult::with_dialect(&text[..], &dialect).tables()
+    }
+
+    /// Query returning the tables of each result, keyed by result name.
+    /// A script with several `yield()` calls returns one entry per yield.
+    pub async fn query_results(
+        &self,
+        query: Option<Query>,
+    ) -> Result<HashMap<String, Vec<FluxTable>>, RequestError> {
+        self.query_results_with_options(query, &RequestOptions::default())
+            .await
+    }
+
+    /// Query returning the tables of each result with per-request options
+    pub async fn query_results_with_options(
+        &self,
+        query: Option<Query>,
+        options: &RequestOptions,
+    ) -> Result<HashMap<String, Vec<FluxTable>>, RequestError> {
+        let (response, dialect) = self.send_query(query, options).await?;
+        let text = response.text().await.context(ReqwestProcessing)?;
+        QueryTableResult::with_dialect(&text[..], &dialect).results()
     }
 
     /// Query return an iterator over the raw results
@@ -162,25 +326,98 @@ impl Client {
         &self,
         query: Option<Query>,
     ) -> Result<QueryTableIter, RequestError> {
+        let (response, dialect) = self.send_query(query, &RequestOptions::default()).await?;
+        let text = response.text().await.context(ReqwestProcessing)?;
+
+        Ok(QueryTableIter::new(text, dialect))
+    }
+
+    /// Query returning a stream of records, parsed as the response arrives.
+    ///
+    /// Dropping the stream closes the connection, which stops the query on
+    /// the server.
+    pub async fn query_stream(&self, query: Option<Query>) -> Result<QueryStream, RequestError> {
+        self.query_stream_with_options(query, &RequestOptions::default())
+            .await
+    }
+
+    /// Query returning a stream of records with per-request options
+    pub async fn query_stream_with_options(
+        &self,
+        query: Option<Query>,
+        options: &RequestOptions,
+    ) -> Result<QueryStream, RequestError> {
+        let (response, dialect) = self.send_query(query, options).await?;
+
+        Ok(QueryStream::new(Box::pin(response.bytes_stream()), dialect))
+    }
+
+    /// Stream the raw CSV response of a query into `writer` without
+    /// parsing it, e.g. for exports.
+    ///
+    /// Returns the number of bytes written.
+    pub async fn query_csv_raw<W>(
+        &self,
+        query: Option<Query>,
+        writer: &mut W,
+    ) -> Result<u64, RequestError>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        self.query_csv_raw_with_options(query, &RequestOptions::default(), writer)
+            .await
+    }
+
+    /// Stream the raw CSV response of a query into `writer` with per-request
+    /// options.
+    pub async fn query_csv_raw_with_options<W>(
+        &self,
+        query: Option<Query>,
+        options: &RequestOptions,
+        writer: &mut W,
+    ) -> Result<u64, RequestError>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        let query = query.unwrap_or_default();
+        let request = self.query_request(&query, options)?;
+
+        stream_response_to(request, writer).await
+    }
+
+    fn query_request(
+        &self,
+        query: &Query,
+        options: &RequestOptions,
+    ) -> Result<RequestBuilder, RequestError> {
         let req_url = self.url("/api/v2/query");
-        let body = serde_json::to_string(&query.unwrap_or_default()).context(Serializing)?;
+        let body = serde_json::to_string(query).context(Serializing)?;
 
-        let response = self
+        let request = self
             .request(Method::POST, &req_url)
             .header("Accepting-Encoding", "identity")
             .header("Content-Type", "application/json")
-            .query(&[("org", &self.org)])
-            .body(body)
+            .body(body);
+
+        Ok(options.apply(&self.org, request))
+    }
+
+    /// Send a query, returns the successful response and the dialect it was
+    /// produced with
+    async fn send_query(
+        &self,
+        query: Option<Query>,
+        options: &RequestOptions,
+    ) -> Result<(Response, Dialect), RequestError> {
+        let query = query.unwrap_or_default();
+        let response = self
+            .query_request(&query, options)?
             .send()
             .await
             .context(ReqwestProcessing)?;
 
         match response.status() {
-            StatusCode::OK => {
-                let text = response.text().await.context(ReqwestProcessing)?;
-
-                Ok(QueryTableIter::new(text))
-            }
+            StatusCode::OK => Ok((response, query.dialect.unwrap_or_default())),
             status => {
                 let text = response.text().await.context(ReqwestProcessing)?;
                 Http { status, text }.fail()?
@@ -244,189 +481,26 @@ impl Client {
             }
         }
     }
-
-    /// Returns bucket measurements
-    ///
-    /// # Arguments
-    ///
-    /// * `bucket` - The bucket name
-    /// * `start` - Optional start time. Default is `-30d`
-    /// * `stop` - Optional stop time. Default is `now()`
-    pub async fn list_measurements(
-        &self,
-        bucket: &str,
-        start: Option<&str>,
-        stop: Option<&str>,
-    ) -> Result<Vec<String>, RequestError> {
-        let mut params = vec![];
-        params.push(format!(r#"bucket: "{bucket}""#));
-        if let Some(start) = start {
-            params.push(format!("start: {start}"));
-        }
-        if let Some(stop) = stop {
-            params.push(format!("stop: {stop}"));
-        }
-        let params = params.join(", ");
-
-        let query = Query::new(format!(
-            r#"import "influxdata/influxdb/schema"
-
-            schema.measurements({params})"#
-        ));
-        self.exec_schema_query(query).await
-    }
-
-    /// List field keys for measurement
-    ///
-    /// # Arguments
-    ///
-    /// * `bucket` - The bucket name
-    /// * `measurement` - The measurement name
-    /// * `start` - Optional start time. Default is `-30d`
-    /// * `stop` - Optional stop time. Default is `now()`
-    pub async fn list_measurement_field_keys(
-        &self,
-        bucket: &str,
-        measurement: &str,
-        start: Option<&str>,
-        stop: Option<&str>,
-    ) -> Result<Vec<String>, RequestError> {
-        let mut params = vec![];
-        params.push(format!(r#"bucket: "{bucket}""#));
-        params.push(format!(r#"measurement: "{measurement}""#));
-        if let Some(start) = start {
-            params.push(format!("start: {start}"));
-        }
-        if let Some(stop) = stop {
-            params.push(format!("stop: {stop}"));
-        }
-        let params = params.join(", ");
-
-        let query = Query::new(format!(
-            r#"import "influxdata/influxdb/schema"
-
-            schema.measurementFieldKeys({params})"#,
-        ));
-        self.exec_schema_query(query).await
-    }
-
-    /// List all tag values for measurement tag
-    ///
-    /// # Arguments
-    ///
-    /// * `bucket` - The bucket name
-    /// * `measurement` - The measurement name
-    /// * `tag` - The tag name
-    /// * `start` - Optional start time. Default is `-30d`
-    /// * `stop` - Optional stop time. Default is `now()`
-    pub async fn list_measurement_tag_values(
-        &self,
-        bucket: &str,
-        measurement: &str,
-        tag: &str,
-        start: Option<&str>,
-        stop: Option<&str>,
-    ) -> Result<Vec<String>, RequestError> {
-        let mut params = vec![];
-        params.push(format!(r#"bucket: "{bucket}""#));
-        params.push(format!(r#"measurement: "{measurement}""#));
-        params.push(format!(r#"tag: "{tag}""#));
-        if let Some(start) = start {
-            params.push(format!("start: {start}"));
-        }
-        if let Some(stop) = stop {
-            params.push(format!("stop: {stop}"));
-        }
-        let params = params.join(", ");
-
-        let query = Query::new(format!(
-            r#"import "influxdata/influxdb/schema"
-
-            schema.measurementTagValues({params})"#,
-        ));
-        self.exec_schema_query(query).await
-    }
-
-    /// List all tag keys for measurement
-    ///
-    /// # Arguments
-    ///
-    /// * `bucket` - The bucket name
-    /// * `measurement` - The measurement name
-    /// * `start` - Optional start time. Default is `-30d`
-    /// * `stop` - Optional stop time. Default is `now()`
-    pub async fn list_measurement_tag_keys(
-        &self,
-        bucket: &str,
-        measurement: &str,
-        start: Option<&str>,
-        stop: Option<&str>,
-    ) -> Result<Vec<String>, RequestError> {
-        let mut params = vec![];
-        params.push(format!(r#"bucket: "{bucket}""#));
-        params.push(format!(r#"measurement: "{measurement}""#));
-        if let Some(start) = start {
-            params.push(format!("start: {start}"));
-        }
-        if let Some(stop) = stop {
-            params.push(format!("stop: {stop}"));
-        }
-        let params = params.join(", ");
-
-        let query = Query::new(format!(
-            r#"import "influxdata/influxdb/schema"
-
-            schema.measurementTagKeys({params})"#,
-        ));
-        self.exec_schema_query(query).await
-    }
-
-    async fn exec_schema_query(&self, query: Query) -> Result<Vec<String>, RequestError> {
-        let req_url = self.url("/api/v2/query");
-        let body = serde_json::to_string(&query).context(Serializing)?;
-
-        let response = self
-            .request(Method::POST, &req_url)
-            .header("Accepting-Encoding", "identity")
-            .header("Content-Type", "application/json")
-            .query(&[("org", &self.org)])
-            .body(body)
-            .send()
-            .await
-            .context(ReqwestProcessing)?;
-
-        match response.status() {
-            StatusCode::OK => {
-                let text: String = response.text().await.unwrap();
-
-                let mut reader = csv::ReaderBuilder::new()
-                    .has_headers(true)
-                    .comment(Some(b'#'))
-                    .from_reader(text.as_bytes());
-
-                Ok(reader
-                    .records()
-                    .flatten()
-                    .flat_map(|r: StringRecord| r.get(3).map(|s| s.to_owned()))
-                    .collect())
-            }
-            status => {
-                let text = response.text().await.context(ReqwestProcessing)?;
-                Http { status, text }.fail()?
-            }
-        }
-    }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-enum DataType {
+/// Datatype of a column, from the `#datatype` annotation
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum DataType {
+    /// `string`
     String,
+    /// `double`
     Double,
+    /// `boolean`
     Bool,
+    /// `long`
     Long,
+    /// `unsignedLong`
     UnsignedLong,
+    /// `duration`
     Duration,
+    /// `base64Binary`
     Base64Binary,
+    /// `dateTime:RFC3339` or `dateTime:RFC3339Nano`
     TimeRFC,
 }
 
@@ -451,11 +525,38 @@ impl FromStr for DataType {
     }
 }
 
-struct FluxColumn {
-    name: String,
-    data_type: DataType,
-    group: bool,
-    default_value: String,
+/// Column of a flux table
+#[derive(Clone, Debug, PartialEq)]
+pub struct FluxColumn {
+    /// Column name
+    pub name: String,
+    /// Column datatype
+    pub data_type: DataType,
+    /// Whether the column is part of the group key
+    pub group: bool,
+    /// Value used when a row has no value for the column
+    pub default: String,
+}
+
+/// Represents a flux table returned from a query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FluxTable {
+    /// Table columns
+    pub columns: Vec<FluxColumn>,
+    /// Table records
+    pub records: Vec<FluxRecord>,
+}
+
+impl FluxTable {
+    /// Columns that are part of the group key
+    pub fn group_key(&self) -> Vec<&FluxColumn> {
+        self.columns.iter().filter(|c| c.group).collect()
+    }
+
+    /// Name of the result the table belongs to, the `result` column
+    pub fn result(&self) -> Option<&str> {
+        self.records.first().and_then(|r| r.get_str("result"))
+    }
 }
 
 /// Represents a flux record returned from a query.
@@ -467,16 +568,210 @@ pub struct FluxRecord {
     pub values: GenericMap,
 }
 
+impl FluxRecord {
+    /// Value of the column `name`
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+
+    /// Time of the record, the `_time` column
+    pub fn time(&self) -> Option<DateTime<FixedOffset>> {
+        self.get_time("_time")
+    }
+
+    /// Inclusive lower time bound of the query, the `_start` column
+    pub fn start(&self) -> Option<DateTime<FixedOffset>> {
+        self.get_time("_start")
+    }
+
+    /// Exclusive upper time bound of the query, the `_stop` column
+    pub fn stop(&self) -> Option<DateTime<FixedOffset>> {
+        self.get_time("_stop")
+    }
+
+    /// Measurement name, the `_measurement` column
+    pub fn measurement(&self) -> Option<&str> {
+        self.get_str("_measurement")
+    }
+
+    /// Field name, the `_field` column
+    pub fn field(&self) -> Option<&str> {
+        self.get_str("_field")
+    }
+
+    /// Field value, the `_value` column
+    pub fn value(&self) -> Option<&Value> {
+        self.get("_value")
+    }
+
+    fn get_time(&self, name: &str) -> Option<DateTime<FixedOffset>> {
+        match self.get(name) {
+            Some(Value::TimeRFC(t)) => Some(*t),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn get_str(&self, name: &str) -> Option<&str> {
+        match self.get(name) {
+            Some(Value::String(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn get_long(&self, name: &str) -> Option<i64> {
+        match self.get(name) {
+            Some(Value::Long(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Duration stored as nanoseconds
+    fn get_duration(&self, name: &str) -> Duration {
+        Duration::from_nanos(self.get_long(name).unwrap_or_default().max(0) as u64)
+    }
+}
+
+/// Records of a profiled query, with the profiler results split off
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct ProfiledQueryResult {
+    /// Data records
+    pub records: Vec<FluxRecord>,
+    /// Rows of the `profiler/query` table
+    pub query_profiles: Vec<QueryProfile>,
+    /// Rows of the `profiler/operator` table
+    pub operator_profiles: Vec<OperatorProfile>,
+}
+
+impl ProfiledQueryResult {
+    fn new(records: Vec<FluxRecord>) -> Self {
+        let mut result = Self::default();
+        for record in records {
+            match record.measurement() {
+                Some("profiler/query") => result
+                    .query_profiles
+                    .push(QueryProfile::from_record(&record)),
+                Some("profiler/operator") => result
+                    .operator_profiles
+                    .push(OperatorProfile::from_record(&record)),
+                _ => result.records.push(record),
+            }
+        }
+        result
+    }
+}
+
+/// Profile of a whole query, a row of the `profiler/query` table
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct QueryProfile {
+    /// Total time spent on the query
+    pub total_duration: Duration,
+    /// Time spent compiling
+    pub compile_duration: Duration,
+    /// Time spent queued
+    pub queue_duration: Duration,
+    /// Time spent planning
+    pub plan_duration: Duration,
+    /// Time spent queued again after planning
+    pub requeue_duration: Duration,
+    /// Time spent executing
+    pub execute_duration: Duration,
+    /// Number of goroutines allocated to the query
+    pub concurrency: i64,
+    /// Maximum number of bytes allocated at once
+    pub max_allocated: i64,
+    /// Total number of bytes allocated
+    pub total_allocated: i64,
+    /// Errors raised while executing the query
+    pub runtime_errors: Option<String>,
+    /// Query plan, the `flux/query-plan` column
+    pub query_plan: Option<String>,
+    /// Number of bytes scanned from storage
+    pub scanned_bytes: Option<i64>,
+    /// Number of values scanned from storage
+    pub scanned_values: Option<i64>,
+}
+
+impl QueryProfile {
+    /// Read a profile from a `profiler/query` record
+    pub fn from_record(record: &FluxRecord) -> Self {
+        Self {
+            total_duration: record.get_duration("TotalDuration"),
+            compile_duration: record.get_duration("CompileDuration"),
+            queue_duration: record.get_duration("QueueDuration"),
+            plan_duration: record.get_duration("PlanDuration"),
+            requeue_duration: record.get_duration("RequeueDuration"),
+            execute_duration: record.get_duration("ExecuteDuration"),
+            concurrency: record.get_long("Concurrency").unwrap_or_default(),
+            max_allocated: record.get_long("MaxAllocated").unwrap_or_default(),
+            total_allocated: record.get_long("TotalAllocated").unwrap_or_default(),
+            runtime_errors: record
+                .get_str("RuntimeErrors")
+                .filter(|s| !s.is_empty())
+                .map(str::to_owned),
+            query_plan: record
+                .get_str("flux/query-plan")
+                .filter(|s| !s.is_empty())
+                .map(str::to_owned),
+            scanned_bytes: record.get_long("influxdb/scanned-bytes"),
+            scanned_values: record.get_long("influxdb/scanned-values"),
+        }
+    }
+}
+
+/// Profile of a single operator, a row of the `profiler/operator` table
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct OperatorProfile {
+    /// Operator type, e.g. `*universe.filterTransformation`
+    pub r#type: String,
+    /// Operator label, e.g. `filter2`
+    pub label: String,
+    /// Number of times the operator ran
+    pub count: i64,
+    /// Shortest run
+    pub min_duration: Duration,
+    /// Longest run
+    pub max_duration: Duration,
+    /// Total time of all runs
+    pub duration_sum: Duration,
+    /// Average run
+    pub mean_duration: Duration,
+}
+
+impl OperatorProfile {
+    /// Read a profile from a `profiler/operator` record
+    pub fn from_record(record: &FluxRecord) -> Self {
+        Self {
+            r#type: record.get_str("Type").unwrap_or_default().to_owned(),
+            label: record.get_str("Label").unwrap_or_default().to_owned(),
+            count: record.get_long("Count").unwrap_or_default(),
+            min_duration: record.get_duration("MinDuration"),
+            max_duration: record.get_duration("MaxDuration"),
+            duration_sum: record.get_duration("DurationSum"),
+            // The mean is a double, unlike the other durations
+            mean_duration: match record.get("MeanDuration") {
+                Some(Value::Double(v)) => Duration::from_nanos(v.round() as u64),
+                _ => Duration::ZERO,
+            },
+        }
+    }
+}
+
 struct FluxTableMetadata {
     position: i32,
     columns: Vec<FluxColumn>,
 }
 
-struct QueryTableResult<'a> {
-    csv_reader: csv::Reader<&'a [u8]>,
+/// Row by row parser of annotated CSV, shared by the buffered and the
+/// streaming query APIs.
+struct FluxCsvParser {
     table_position: i32,
     table_changed: bool,
     table: Option<FluxTableMetadata>,
+    header: bool,
+    annotations: Vec<Annotations>,
+    comment_prefix: Option<String>,
+    parsing_state: ParsingState,
+    data_type_annotation_found: bool,
 }
 
 #[derive(PartialEq)]
@@ -486,162 +781,293 @@ enum ParsingState {
     Error,
 }
 
-impl<'a> QueryTableResult<'a> {
-    fn new(text: &'a str) -> Self {
-        let reader = csv::ReaderBuilder::new()
-            .has_headers(false)
-            .flexible(true)
-            .from_reader(text.as_bytes());
+impl FluxCsvParser {
+    /// Parser for a response produced with `dialect`. Without annotations,
+    /// all values are strings and tables are separated by empty lines.
+    /// Without a header, columns are named by their position.
+    fn new(dialect: &Dialect) -> Self {
         Self {
-            csv_reader: reader,
             table_position: 0,
             table_changed: false,
             table: None,
+            header: dialect.header.unwrap_or(true),
+            annotations: dialect.annotations.clone(),
+            comment_prefix: dialect.comment_prefix.clone().filter(|p| !p.is_empty()),
+            parsing_state: ParsingState::Normal,
+            data_type_annotation_found: false,
         }
     }
-}
 
-impl<'a> FallibleIterator for QueryTableResult<'a> {
-    type Item = FluxRecord;
-    type Error = RequestError;
-
-    fn next(&mut self) -> Result<Option<FluxRecord>, RequestError> {
-        // Hold the FluxRecord to be returned.
-        let record: FluxRecord;
+    fn start_table(&mut self, columns: usize) {
+        self.table = Some(FluxTableMetadata {
+            position: self.table_position,
+            columns: (0..columns)
+                .map(|i| FluxColumn {
+                    name: if self.header {
+                        String::from("")
+                    } else {
+                        i.to_string()
+                    },
+                    data_type: DataType::String,
+                    group: false,
+                    default: String::from(""),
+                })
+                .collect(),
+        });
+        self.table_position += 1;
+        self.table_changed = true;
+        self.data_type_annotation_found = false;
+    }
 
-        self.table_changed = false;
-        let mut row = StringRecord::new();
-        let mut parsing_state = ParsingState::Normal;
-        let mut data_type_annotation_found = false;
-        loop {
-            if !self.csv_reader.read_record(&mut row).unwrap() {
-                // EOF
+    /// Parse a single row, returns a record for data rows.
+    fn parse_row(
+        &mut self,
+        row: &StringRecord,
+        after_empty_line: bool,
+    ) -> Result<Option<FluxRecord>, RequestError> {
+        if row.len() <= 1 {
+            return Ok(None);
+        }
+        if let Some(s) = row.get(0) {
+            let is_annotation = matches!(s, "#datatype" | "#group" | "#default");
+            if !is_annotation
+                && self
+                    .comment_prefix
+                    .as_ref()
+                    .is_some_and(|p| s.starts_with(p.as_str()))
+            {
                 return Ok(None);
             }
-            if row.len() <= 1 {
-                continue;
-            }
-            if let Some(s) = row.get(0) {
-                if !s.is_empty() && s.chars().nth(0).unwrap() == '#' {
-                    // Finding new table, prepare for annotation parsing
-                    if parsing_state == ParsingState::Normal {
-                        self.table = Some(FluxTableMetadata {
-                            position: self.table_position,
-                            columns: Vec::new(),
-                        });
-                        self.table_position += 1;
-                        self.table_changed = true;
-                        for _ in 1..row.len() {
-                            self.table.as_mut().unwrap().columns.push(FluxColumn {
-                                name: String::from(""),
-                                data_type: DataType::String,
-                                group: false,
-                                default_value: String::from(""),
-                            });
-                        }
-                        parsing_state = ParsingState::Annotation;
-                    }
+            if is_annotation {
+                // Finding new table, prepare for annotation parsing
+                if self.parsing_state == ParsingState::Normal {
+                    self.start_table(row.len() - 1);
+                    self.parsing_state = ParsingState::Annotation;
+                }
+            } else if self.annotations.is_empty()
+                && self.parsing_state == ParsingState::Normal
+                && (self.table.is_none() || after_empty_line)
+            {
+                // Without annotations a table starts at its header row
+                self.start_table(row.len() - 1);
+                if self.header {
+                    self.parsing_state = ParsingState::Annotation;
                 }
             }
-            if self.table.is_none() {
-                return Err(RequestError::Deserializing {
-                    text: String::from("annotations not found"),
-                });
-            }
-            if row.len() - 1 != self.table.as_ref().unwrap().columns.len() {
-                return Err(RequestError::Deserializing {
-                    text: format!(
-                        "row has different number of columns than the table: {} vs {}",
-                        row.len() - 1,
-                        self.table.as_ref().unwrap().columns.len(),
-                    ),
-                });
-            }
-            if let Some(s) = row.get(0) {
-                match s {
-                    "" => {
-                        match parsing_state {
-                            ParsingState::Annotation => {
-                                // Parse column name (csv header)
-                                if !data_type_annotation_found {
-                                    return Err(RequestError::Deserializing {
-                                        text: String::from("datatype annotation not found"),
-                                    });
-                                }
-                                if row.get(1).unwrap() == "error" {
-                                    parsing_state = ParsingState::Error;
-                                } else {
-                                    for i in 1..row.len() {
-                                        let column =
-                                            &mut self.table.as_mut().unwrap().columns[i - 1];
-                                        column.name = String::from(row.get(i).unwrap());
-                                    }
-                                    parsing_state = ParsingState::Normal;
-                                }
-                                continue;
-                            }
-                            ParsingState::Error => {
-                                let msg = if row.len() > 1 && !row.get(1).unwrap().is_empty() {
-                                    row.get(1).unwrap()
-                                } else {
-                                    "unknown query error"
-                                };
-                                let mut reference = String::from("");
-                                if row.len() > 2 && !row.get(2).unwrap().is_empty() {
-                                    let s = row.get(2).unwrap();
-                                    reference = format!(",{}", s);
-                                }
+        }
+        if self.table.is_none() {
+            return Err(RequestError::Deserializing {
+                text: String::from("annotations not found"),
+            });
+        }
+        if row.len() - 1 != self.table.as_ref().unwrap().columns.len() {
+            return Err(RequestError::Deserializing {
+                text: format!(
+                    "row has different number of columns than the table: {} vs {}",
+                    row.len() - 1,
+                    self.table.as_ref().unwrap().columns.len(),
+                ),
+            });
+        }
+        if let Some(s) = row.get(0) {
+            match s {
+                "" => {
+                    match self.parsing_state {
+                        ParsingState::Annotation if self.header => {
+                            // Parse column name (csv header)
+                            if !self.data_type_annotation_found
+                                && self.annotations.contains(&Annotations::Datatype)
+                            {
                                 return Err(RequestError::Deserializing {
-                                    text: format!("{}{}", msg, reference),
+                                    text: String::from("datatype annotation not found"),
                                 });
                             }
-                            _ => {}
+                            if row.get(1).unwrap() == "error" {
+                                self.parsing_state = ParsingState::Error;
+                            } else {
+                                for i in 1..row.len() {
+                                    let column = &mut self.table.as_mut().unwrap().columns[i - 1];
+                                    column.name = String::from(row.get(i).unwrap());
+                                }
+                                self.parsing_state = ParsingState::Normal;
+                            }
+                            return Ok(None);
                         }
-                        let mut values = BTreeMap::new();
-                        for i in 1..row.len() {
-                            let column = &self.table.as_mut().unwrap().columns[i - 1];
-                            let mut v = row.get(i).unwrap();
-                            if v.is_empty() {
-                                v = &column.default_value[..];
+                        ParsingState::Error => {
+                            let msg = if row.len() > 1 && !row.get(1).unwrap().is_empty() {
+                                row.get(1).unwrap()
+                            } else {
+                                "unknown query error"
+                            };
+                            let mut reference = String::from("");
+                            if row.len() > 2 && !row.get(2).unwrap().is_empty() {
+                                let s = row.get(2).unwrap();
+                                reference = format!(",{}", s);
                             }
-                            let value = parse_value(v, column.data_type, column.name.as_str())?;
-                            values.entry(column.name.clone()).or_insert(value);
+                            return Err(RequestError::Deserializing {
+                                text: format!("{}{}", msg, reference),
+                            });
                         }
-                        record = FluxRecord {
-                            table: self.table.as_ref().unwrap().position,
-                            values,
-                        };
-                        break;
+                        _ => {}
                     }
-                    "#datatype" => {
-                        data_type_annotation_found = true;
-                        for i in 1..row.len() {
-                            let column = &mut self.table.as_mut().unwrap().columns[i - 1];
-                            let dt = DataType::from_str(row.get(i).unwrap())?;
-                            column.data_type = dt;
+                    let mut values = BTreeMap::new();
+                    for i in 1..row.len() {
+                        let column = &self.table.as_mut().unwrap().columns[i - 1];
+                        let mut v = row.get(i).unwrap();
+                        if v.is_empty() {
+                            v = &column.default[..];
                         }
+                        let value = parse_value(v, column.data_type, column.name.as_str())?;
+                        values.entry(column.name.clone()).or_insert(value);
                     }
-                    "#group" => {
-                        for i in 1..row.len() {
-                            let column = &mut self.table.as_mut().unwrap().columns[i - 1];
-                            column.group = row.get(i).unwrap() == "true";
-                        }
+                    self.parsing_state = ParsingState::Normal;
+                    return Ok(Some(FluxRecord {
+                        table: self.table.as_ref().unwrap().position,
+                        values,
+                    }));
+                }
+                "#datatype" => {
+                    self.data_type_annotation_found = true;
+                    for i in 1..row.len() {
+                        let column = &mut self.table.as_mut().unwrap().columns[i - 1];
+                        let dt = DataType::from_str(row.get(i).unwrap())?;
+                        column.data_type = dt;
                     }
-                    "#default" => {
-                        for i in 1..row.len() {
-                            let column = &mut self.table.as_mut().unwrap().columns[i - 1];
-                            column.default_value = String::from(row.get(i).unwrap());
-                        }
+                }
+                "#group" => {
+                    for i in 1..row.len() {
+                        let column = &mut self.table.as_mut().unwrap().columns[i - 1];
+                        column.group = row.get(i).unwrap() == "true";
                     }
-                    _ => {
-                        return Err(RequestError::Deserializing {
-                            text: format!("invalid first cell: {}", s),
-                        });
+                }
+                "#default" => {
+                    for i in 1..row.len() {
+                        let column = &mut self.table.as_mut().unwrap().columns[i - 1];
+                        column.default = String::from(row.get(i).unwrap());
                     }
                 }
+                _ => {
+                    return Err(RequestError::Deserializing {
+                        text: format!("invalid first cell: {}", s),
+                    });
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn csv_reader<R: std::io::Read>(dialect: &Dialect, reader: R) -> csv::Reader<R> {
+    let delimiter = dialect
+        .delimiter
+        .as_ref()
+        .and_then(|d| d.bytes().next())
+        .unwrap_or(b',');
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(reader)
+}
+
+/// Empty lines are skipped by the reader, but they separate tables. The
+/// reported position is where the reader started looking for the record, so
+/// it points at the skipped empty line if any.
+fn after_empty_line(text: &[u8], row: &StringRecord) -> bool {
+    row.position()
+        .and_then(|p| text.get(p.byte() as usize))
+        .is_some_and(|c| *c == b'\n' || *c == b'\r')
+}
+
+fn read_row<R: std::io::Read>(
+    reader: &mut csv::Reader<R>,
+    row: &mut StringRecord,
+) -> Result<bool, RequestError> {
+    reader
+        .read_record(row)
+        .map_err(|e| RequestError::Deserializing {
+            text: e.to_string(),
+        })
+}
+
+struct QueryTableResult<'a> {
+    csv_reader: csv::Reader<&'a [u8]>,
+    text: &'a [u8],
+    parser: FluxCsvParser,
+}
+
+impl<'a> QueryTableResult<'a> {
+    #[cfg(test)]
+    fn new(text: &'a str) -> Self {
+        Self::with_dialect(text, &Query::default().dialect.unwrap_or_default())
+    }
+
+    fn with_dialect(text: &'a str, dialect: &Dialect) -> Self {
+        Self {
+            csv_reader: csv_reader(dialect, text.as_bytes()),
+            text: text.as_bytes(),
+            parser: FluxCsvParser::new(dialect),
+        }
+    }
+}
+
+impl<'a> FallibleIterator for QueryTableResult<'a> {
+    type Item = FluxRecord;
+    type Error = RequestError;
+
+    fn next(&mut self) -> Result<Option<FluxRecord>, RequestError> {
+        self.parser.table_changed = false;
+        let mut row = StringRecord::new();
+        while read_row(&mut self.csv_reader, &mut row)? {
+            let after_empty_line = after_empty_line(self.text, &row);
+            if let Some(record) = self.parser.parse_row(&row, after_empty_line)? {
+                return Ok(Some(record));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl QueryTableResult<'_> {
+    /// Collects records into tables. A new table starts with each annotation
+    /// block and whenever the `result` or `table` column changes within a
+    /// block.
+    fn tables(mut self) -> Result<Vec<FluxTable>, RequestError> {
+        let mut tables: Vec<FluxTable> = vec![];
+        let mut table_id = (None, None);
+        while let Some(mut record) = self.next()? {
+            let id = (record.get("result").cloned(), record.get("table").cloned());
+            if self.parser.table_changed || tables.is_empty() || id != table_id {
+                tables.push(FluxTable {
+                    columns: self
+                        .parser
+                        .table
+                        .as_ref()
+                        .map(|t| t.columns.clone())
+                        .unwrap_or_default(),
+                    records: vec![],
+                });
+            }
+            table_id = id;
+            record.table = tables.len() as i32 - 1;
+            tables.last_mut().unwrap().records.push(record);
+        }
+        Ok(tables)
+    }
+
+    /// Collects tables by the name of the result they belong to, the
+    /// `result` column. Tables are numbered per result.
+    fn results(self) -> Result<HashMap<String, Vec<FluxTable>>, RequestError> {
+        let mut results: HashMap<String, Vec<FluxTable>> = HashMap::new();
+        for mut table in self.tables()? {
+            let name = table.result().unwrap_or_default().to_owned();
+            let tables = results.entry(name).or_default();
+            for record in &mut table.records {
+                record.table = tables.len() as i32;
             }
+            tables.push(table);
         }
-        Ok(Some(record))
+        Ok(results)
     }
 }
 
@@ -761,6 +1187,7 @@ fn parse_value(s: &str, t: DataType, name: &str) -> Result<Value, RequestError>
 mod tests {
     use super::*;
     use crate::FromDataPoint;
+    use futures::StreamExt;
     use mockito::{mock, Matcher};
 
     #[derive(Default, FromDataPoint)]
@@ -848,6 +1275,86 @@ mod tests {
         mock_server.assert();
     }
 
+    #[tokio::test]
+    async fn query_csv_raw() {
+        let token = "some-token";
+        let org = "some-org";
+        let csv = ",result,table,_value\r\n,_result,0,1\r\n";
+
+        let mock_server = mock("POST", "/api/v2/query")
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .match_query(Matcher::UrlEncoded("org".into(), org.into()))
+            .with_body(csv)
+            .create();
+
+        let client = Client::new(mockito::server_url(), org, token);
+
+        let mut out = Vec::new();
+        let written = client
+            .query_csv_raw(Some(Query::new("buckets()".to_string())), &mut out)
+            .await
+            .unwrap();
+
+        mock_server.assert();
+        assert_eq!(written, csv.len() as u64);
+        assert_eq!(out, csv.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn query_with_options() {
+        let token = "some-token";
+        let org_id = "some-org_id";
+
+        let mock_server = mock("POST", "/api/v2/query")
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .match_header("X-Request-Id", "abc")
+            .match_query(Matcher::UrlEncoded("orgID".into(), org_id.into()))
+            .create();
+
+        let client = Client::new(mockito::server_url(), "org", token);
+
+        let options = RequestOptions::new()
+            .org_id(org_id)
+            .header("X-Request-Id", "abc")
+            .timeout(std::time::Duration::from_secs(5));
+        let _result = client.query_with_options::<Empty>(None, &options).await;
+
+        mock_server.assert();
+    }
+
+    #[tokio::test]
+    async fn query_stream() {
+        let token = "some-token";
+        let org = "some-org";
+
+        let mock_server = mock("POST", "/api/v2/query")
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .match_query(Matcher::UrlEncoded("org".into(), "other-org".into()))
+            .with_body("#datatype,string,long,double\n#group,false,false,false\n#default,_result,,\n,result,table,_value\n,,0,1.5\n,,0,2.5\n")
+            .create();
+
+        let client = Client::new(mockito::server_url(), org, token);
+
+        let options = RequestOptions::new().org("other-org");
+        let mut stream = client
+            .query_stream_with_options(None, &options)
+            .await
+            .unwrap();
+        let mut values = vec![];
+        while let Some(record) = stream.next().await {
+            values.push(record.unwrap().value().cloned());
+        }
+
+        mock_server.assert();
+        assert_eq!(
+            values,
+            vec![
+                Some(Value::Double(OrderedFloat::from(1.5))),
+                Some(Value::Double(OrderedFloat::from(2.5)))
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn query_analyze() {
         let token = "some-token";
@@ -987,4 +1494,261 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_query_table_result_tables() {
+        let text = "#datatype,string,long,dateTime:RFC3339,double,string,string,string
+#group,false,false,false,false,true,true,true
+#default,_result,,,,,,
+,result,table,_time,_value,_field,_measurement,host
+,,0,2020-02-18T10:34:08Z,1.4,usage,cpu,a
+,,0,2020-02-18T10:35:08Z,1.5,usage,cpu,a
+,,1,2020-02-18T10:34:08Z,2.4,usage,cpu,b
+
+#datatype,string,long,dateTime:RFC3339,long,string,string,string
+#group,false,false,false,false,true,true,true
+#default,_result,,,,,,
+,result,table,_time,_value,_field,_measurement,host
+,,2,2020-02-18T10:34:08Z,7,count,cpu,a
+";
+        let tables = QueryTableResult::new(text).tables().unwrap();
+
+        assert_eq!(tables.len(), 3);
+        assert_eq!(
+            tables.iter().map(|t| t.records.len()).collect::<Vec<_>>(),
+            vec![2, 1, 1]
+        );
+        assert_eq!(
+            tables[0]
+                .group_key()
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["_field", "_measurement", "host"]
+        );
+        assert_eq!(tables[0].columns[0].default, "_result");
+        assert_eq!(tables[2].columns[3].data_type, DataType::Long);
+
+        let record = &tables[1].records[0];
+        assert_eq!(record.table, 1);
+        assert_eq!(record.measurement(), Some("cpu"));
+        assert_eq!(record.field(), Some("usage"));
+        assert_eq!(
+            record.value(),
+            Some(&Value::Double(OrderedFloat::from(2.4)))
+        );
+        assert_eq!(
+            record.time().map(|t| t.to_rfc3339()),
+            Some("2020-02-18T10:34:08+00:00".to_string())
+        );
+        assert_eq!(tables[2].records[0].value(), Some(&Value::Long(7)));
+    }
+
+    #[test]
+    fn test_query_table_result_results() {
+        let text = "#datatype,string,long,dateTime:RFC3339,double
+#group,false,false,false,false
+#default,mean,,,
+,result,table,_time,_value
+,,0,2020-02-18T10:34:08Z,1.5
+,,1,2020-02-18T10:34:08Z,2.5
+
+#datatype,string,long,dateTime:RFC3339,double
+#group,false,false,false,false
+#default,max,,,
+,result,table,_time,_value
+,,0,2020-02-18T10:34:08Z,3.5
+";
+        let results = QueryTableResult::new(text).results().unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results["mean"].len(), 2);
+        assert_eq!(results["mean"][1].records[0].table, 1);
+        assert_eq!(results["max"].len(), 1);
+        assert_eq!(results["max"][0].result(), Some("max"));
+        assert_eq!(
+            results["max"][0].records[0].value(),
+            Some(&Value::Double(OrderedFloat::from(3.5)))
+        );
+    }
+
+    #[test]
+    fn test_query_table_result_dialect() {
+        let dialect = Dialect {
+            delimiter: Some(";".to_string()),
+            comment_prefix: Some("//".to_string()),
+            annotations: vec![Annotations::Group],
+            ..Dialect::default()
+        };
+        let text = "// exported
+#group;false;false;true
+;result;table;host
+;_result;0;a
+
+#group;false;false;true
+;result;table;host
+;_result;1;b
+";
+        let tables = QueryTableResult::with_dialect(text, &dialect)
+            .tables()
+            .unwrap();
+
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[1].group_key()[0].name, "host");
+        assert_eq!(
+            tables[1].records[0].get("table"),
+            Some(&Value::String("1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_query_table_result_no_annotations() {
+        let dialect = Dialect {
+            header: Some(false),
+            ..Dialect::default()
+        };
+        let text = ",_result,0,a
+,_result,0,b
+
+,_result,1,c
+";
+        let tables = QueryTableResult::with_dialect(text, &dialect)
+            .tables()
+            .unwrap();
+
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].records.len(), 2);
+        assert_eq!(
+            tables[1].records[0].get("2"),
+            Some(&Value::String("c".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn query_stream_chunks() {
+        let chunks: Vec<reqwest::Result<Bytes>> = vec![
+            "#datatype,string,long,string\n#group,false,false,false\n#def",
+            "ault,_result,,\n,result,table,_value\n,,0,\"a\n",
+            "b\"\n,,0,c",
+            "\n\n#datatype,string,long,string\n#group,false,false,false\n",
+            "#default,_result,,\n,result,table,_value\n,,1,d",
+        ]
+        .into_iter()
+        .map(|c| Ok(Bytes::from(c)))
+        .collect();
+        let mut stream = QueryStream::new(
+            Box::pin(futures::stream::iter(chunks)),
+            Query::default().dialect.unwrap(),
+        );
+
+        let mut records = vec![];
+        while let Some(record) = stream.next().await {
+            records.push(record.unwrap());
+        }
+
+        assert_eq!(
+            records
+                .iter()
+                .map(|r| (r.table, r.value().cloned()))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, Some(Value::String("a\nb".to_string()))),
+                (0, Some(Value::String("c".to_string()))),
+                (1, Some(Value::String("d".to_string()))),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn query_stream_chunk_ends_on_empty_line() {
+        let chunks: Vec<reqwest::Result<Bytes>> = vec![
+            ",result,table,_value\n,_result,0,a\n\n",
+            ",result,table,host\n,_result,1,b\n",
+        ]
+        .into_iter()
+        .map(|c| Ok(Bytes::from(c)))
+        .collect();
+        let mut stream =
+            QueryStream::new(Box::pin(futures::stream::iter(chunks)), Dialect::default());
+
+        let mut records = vec![];
+        while let Some(record) = stream.next().await {
+            records.push(record.unwrap());
+        }
+
+        assert_eq!(
+            records
+                .iter()
+                .map(|r| (r.table, r.values.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    0,
+                    vec![
+                        ("result".to_string(), Value::String("_result".to_string())),
+                        ("table".to_string(), Value::String("0".to_string())),
+                        ("_value".to_string(), Value::String("a".to_string())),
+                    ]
+                    .into_iter()
+                    .collect()
+                ),
+                (
+                    1,
+                    vec![
+                        ("result".to_string(), Value::String("_result".to_string())),
+                        ("table".to_string(), Value::String("1".to_string())),
+                        ("host".to_string(), Value::String("b".to_string())),
+                    ]
+                    .into_iter()
+                    .collect()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_profiled_query_result() {
+        let text = "#datatype,string,long,string,double
+#group,false,false,true,false
+#default,_result,,,
+,result,table,_measurement,_value
+,,0,cpu,1.5
+
+#datatype,string,long,string,long,long,long,long,long,long,long,long,long,long,string,string
+#group,false,false,true,false,false,false,false,false,false,false,false,false,false,false,false
+#default,_profiler,,,,,,,,,,,,,,
+,result,table,_measurement,TotalDuration,CompileDuration,QueueDuration,PlanDuration,RequeueDuration,ExecuteDuration,Concurrency,MaxAllocated,TotalAllocated,influxdb/scanned-bytes,RuntimeErrors,flux/query-plan
+,,0,profiler/query,8924700,350900,33800,0,0,8486500,0,2072,0,0,,
+
+#datatype,string,long,string,string,string,long,long,long,long,double
+#group,false,false,true,false,false,false,false,false,false,false
+#default,_profiler,,,,,,,,,
+,result,table,_measurement,Type,Label,Count,MinDuration,MaxDuration,DurationSum,MeanDuration
+,,1,profiler/operator,*universe.filterTransformation,filter2,1,3000,3000,3000,3000
+";
+        let records = QueryTableResult::new(text)
+            .iterator()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let result = ProfiledQueryResult::new(records);
+
+        assert_eq!(result.records.len(), 1);
+        assert_eq!(result.query_profiles.len(), 1);
+        assert_eq!(
+            result.query_profiles[0].total_duration,
+            Duration::from_nanos(8924700)
+        );
+        assert_eq!(result.query_profiles[0].max_allocated, 2072);
+        assert_eq!(result.query_profiles[0].runtime_errors, None);
+        assert_eq!(result.operator_profiles.len(), 1);
+        assert_eq!(result.operator_profiles[0].label, "filter2");
+        assert_eq!(
+            result.operator_profiles[0].duration_sum,
+            Duration::from_nanos(3000)
+        );
+        assert_eq!(
+            result.operator_profiles[0].mean_duration,
+            Duration::from_nanos(3000)
+        );
+    }
 }
@@ -0,0 +1,150 @@
+//! Restore API
+//!
+//! Stream previously backed up metadata and TSM shard archives back into an
+//! InfluxDB instance.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use futures::StreamExt;
+use reqwest::{Body, Method};
+use snafu::ResultExt;
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+use crate::{Client, Http, RequestError, ReqwestProcessing};
+
+impl Client {
+    /// Overwrite the metadata of the bucket specified by `bucket_id` with the
+    /// serialized bucket metadata read from `reader`.
+    ///
+    /// Returns the number of bytes sent.
+    pub async fn restore_bucket<R>(&self, bucket_id: &str, reader: R) -> Result<u64, RequestError>
+    where
+        R: AsyncRead + Send + Sync + 'static,
+    {
+        let url = self.url(&format!("/api/v2/restore/bucket/{}", bucket_id));
+        self.stream_request_from(&url, reader).await
+    }
+
+    /// Overwrite the TSM data of the shard specified by `shard_id` with the
+    /// shard archive read from `reader`.
+    ///
+    /// Returns the number of bytes sent.
+    pub async fn restore_shard<R>(&self, shard_id: u64, reader: R) -> Result<u64, RequestError>
+    where
+        R: AsyncRead + Send + Sync + 'static,
+    {
+        let url = self.url(&format!("/api/v2/restore/shards/{}", shard_id));
+        self.stream_request_from(&url, reader).await
+    }
+
+    /// Overwrite the SQL metadata store with the snapshot read from `reader`.
+    ///
+    /// Returns the number of bytes sent.
+    pub async fn restore_sql<R>(&self, reader: R) -> Result<u64, RequestError>
+    where
+        R: AsyncRead + Send + Sync + 'static,
+    {
+        let url = self.url("/api/v2/restore/sql");
+        self.stream_request_from(&url, reader).await
+    }
+
+    async fn stream_request_from<R>(&self, url: &str, reader: R) -> Result<u64, RequestError>
+    where
+        R: AsyncRead + Send + Sync + 'static,
+    {
+        let sent = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&sent);
+        let body = ReaderStream::new(reader).inspect(move |chunk| {
+            if let Ok(chunk) = chunk {
+                counter.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+            }
+        });
+
+        let response = self
+            .request(Method::POST, url)
+            .header("Content-Type", "application/octet-stream")
+            .body(Body::wrap_stream(body))
+            .send()
+            .await
+            .context(ReqwestProcessing)?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.context(ReqwestProcessing)?;
+            Http { status, text }.fail()?;
+        }
+
+        Ok(sent.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::mock;
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn restore_bucket() {
+        let token = "some-token";
+        let bucket_id = "some-bucket_id";
+
+        let mock_server = mock(
+            "POST",
+            format!("/api/v2/restore/bucket/{}", bucket_id).as_str(),
+        )
+        .match_header("Authorization", format!("Token {}", token).as_str())
+        .match_header("Content-Type", "application/octet-stream")
+        .match_body("bucket-metadata")
+        .with_status(200)
+        .create();
+
+        let client = Client::new(mockito::server_url(), "org", token);
+
+        let reader = Cursor::new(b"bucket-metadata".to_vec());
+        let sent = client.restore_bucket(bucket_id, reader).await.unwrap();
+
+        mock_server.assert();
+        assert_eq!(sent, 15);
+    }
+
+    #[tokio::test]
+    async fn restore_shard() {
+        let token = "some-token";
+
+        let mock_server = mock("POST", "/api/v2/restore/shards/42")
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .match_body("tsm")
+            .with_status(204)
+            .create();
+
+        let client = Client::new(mockito::server_url(), "org", token);
+
+        let sent = client
+            .restore_shard(42, Cursor::new(b"tsm".to_vec()))
+            .await
+            .unwrap();
+
+        mock_server.assert();
+        assert_eq!(sent, 3);
+    }
+
+    #[tokio::test]
+    async fn restore_sql() {
+        let token = "some-token";
+
+        let mock_server = mock("POST", "/api/v2/restore/sql")
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .with_status(500)
+            .create();
+
+        let client = Client::new(mockito::server_url(), "org", token);
+
+        let result = client.restore_sql(Cursor::new(b"sql".to_vec())).await;
+
+        mock_server.assert();
+        assert!(result.is_err());
+    }
+}
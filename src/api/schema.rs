@@ -0,0 +1,637 @@
+//! Schema
+//!
+//! Explore the measurements, fields and tags stored in a bucket
+
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, Offset, TimeZone};
+
+use crate::api::query::DataType;
+use crate::flux::{self, Expr};
+use crate::models::{Query, QueryParam};
+use crate::{Client, RequestError};
+
+/// One end of the time range searched for schema
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeBound {
+    /// Relative to now, e.g. `-30d`
+    Relative(chrono::Duration),
+    /// Point in time
+    Absolute(DateTime<FixedOffset>),
+    /// The current time
+    Now,
+}
+
+impl From<chrono::Duration> for TimeBound {
+    fn from(duration: chrono::Duration) -> Self {
+        Self::Relative(duration)
+    }
+}
+
+impl<Tz: TimeZone> From<DateTime<Tz>> for TimeBound {
+    fn from(time: DateTime<Tz>) -> Self {
+        Self::Absolute(time.with_timezone(&time.offset().fix()))
+    }
+}
+
+impl From<TimeBound> for Expr {
+    fn from(bound: TimeBound) -> Self {
+        match bound {
+            TimeBound::Relative(duration) => Self::Literal(QueryParam::Duration(duration)),
+            TimeBound::Absolute(time) => Self::Literal(QueryParam::Time(time)),
+            TimeBound::Now => Self::Now,
+        }
+    }
+}
+
+/// Time range searched for schema, the last 30 days by default
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeRange {
+    /// Start of the range, inclusive
+    pub start: TimeBound,
+    /// End of the range, exclusive
+    pub stop: TimeBound,
+}
+
+impl TimeRange {
+    /// Range between `start` and `stop`
+    pub fn new(start: impl Into<TimeBound>, stop: impl Into<TimeBound>) -> Self {
+        Self {
+            start: start.into(),
+            stop: stop.into(),
+        }
+    }
+
+    /// Range between `start` and now
+    pub fn since(start: impl Into<TimeBound>) -> Self {
+        Self::new(start, TimeBound::Now)
+    }
+
+    fn args(&self) -> String {
+        format!(
+            "start: {}, stop: {}",
+            Expr::from(self.start),
+            Expr::from(self.stop)
+        )
+    }
+}
+
+impl Default for TimeRange {
+    fn default() -> Self {
+        Self::since(chrono::Duration::days(-30))
+    }
+}
+
+/// Field key of a measurement
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldKey {
+    /// Field name
+    pub name: String,
+    /// Type of the field values
+    pub data_type: DataType,
+}
+
+/// Tag key of a measurement
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagKey {
+    /// Tag name
+    pub name: String,
+    /// Estimated number of series carrying the tag, from
+    /// `influxdb.cardinality`. `None` when no cardinality was returned for
+    /// the tag.
+    pub cardinality: Option<i64>,
+}
+
+impl Client {
+    /// Returns bucket measurements
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket` - The bucket name
+    /// * `range` - Time range to search
+    pub async fn measurements(
+        &self,
+        bucket: &str,
+        range: &TimeRange,
+    ) -> Result<Vec<String>, RequestError> {
+        let query =
+            Query::new(schema_query("measurements", &["bucket"], range)).bind("bucket", bucket);
+        self.exec_schema_query(query).await
+    }
+
+    /// List field keys for measurement, with the type of their values.
+    /// Keys without a value left in the range are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket` - The bucket name
+    /// * `measurement` - The measurement name
+    /// * `range` - Time range to search
+    pub async fn measurement_field_keys(
+        &self,
+        bucket: &str,
+        measurement: &str,
+        range: &TimeRange,
+    ) -> Result<Vec<FieldKey>, RequestError> {
+        // The type of a field is taken from its first value, `first()` right
+        // after the filter is pushed down to the storage so only one point
+        // per series is read
+        let predicate = flux::measurement().eq(flux::param("measurement"));
+        let query = Query::new(format!(
+            r#"import "types"
+
+from(bucket: params.bucket)
+    |> range({range})
+    |> filter(fn: (r) => {predicate})
+    |> first()
+    |> map(fn: (r) => ({{_field: r._field, type:
+        if types.isType(v: r._value, type: "float") then "double"
+        else if types.isType(v: r._value, type: "int") then "long"
+        else if types.isType(v: r._value, type: "uint") then "unsignedLong"
+        else if types.isType(v: r._value, type: "bool") then "boolean"
+        else "string"}}))
+    |> group(columns: ["_field"])
+    |> first(column: "_field")"#,
+            range = range.args(),
+            predicate = predicate,
+        ))
+        .bind("bucket", bucket)
+        .bind("measurement", measurement);
+
+        Ok(self
+            .query_raw(Some(query))
+            .await?
+            .iter()
+            .filter_map(|record| {
+                let name = record.get_str("_field")?.to_owned();
+                let data_type = DataType::from_str(record.get_str("type")?).ok()?;
+                Some(FieldKey { name, data_type })
+            })
+            .collect())
+    }
+
+    /// List all tag values for measurement tag
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket` - The bucket name
+    /// * `measurement` - The measurement name
+    /// * `tag` - The tag name
+    /// * `range` - Time range to search
+    pub async fn measurement_tag_values(
+        &self,
+        bucket: &str,
+        measurement: &str,
+        tag: &str,
+        range: &TimeRange,
+    ) -> Result<Vec<String>, RequestError> {
+        let query = Query::new(schema_query(
+            "measurementTagValues",
+            &["bucket", "measurement", "tag"],
+            range,
+        ))
+        .bind("bucket", bucket)
+        .bind("measurement", measurement)
+        .bind("tag", tag);
+        self.exec_schema_query(query).await
+    }
+
+    /// List all values of a tag across the bucket
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket` - The bucket name
+    /// * `tag` - The tag name
+    /// * `range` - Time range to search
+    pub async fn tag_values(
+        &self,
+        bucket: &str,
+        tag: &str,
+        range: &TimeRange,
+    ) -> Result<Vec<String>, RequestError> {
+        let query = Query::new(schema_query("tagValues", &["bucket", "tag"], range))
+            .bind("bucket", bucket)
+            .bind("tag", tag);
+        self.exec_schema_query(query).await
+    }
+
+    /// List all tag keys for measurement, with an estimate of their
+    /// cardinality. Columns reserved by InfluxDB, like `_field`, are left
+    /// out.
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket` - The bucket name
+    /// * `measurement` - The measurement name
+    /// * `range` - Time range to search
+    pub async fn measurement_tag_keys(
+        &self,
+        bucket: &str,
+        measurement: &str,
+        range: &TimeRange,
+    ) -> Result<Vec<TagKey>, RequestError> {
+        let query = Query::new(schema_query(
+            "measurementTagKeys",
+            &["bucket", "measurement"],
+            range,
+        ))
+        .bind("bucket", bucket)
+        .bind("measurement", measurement);
+        let names: Vec<String> = self
+            .exec_schema_query(query)
+            .await?
+            .into_iter()
+            .filter(|name| !name.starts_with('_'))
+            .collect();
+        if names.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let tables: Vec<String> = names
+            .iter()
+            .map(|name| {
+                let predicate = flux::measurement().eq(flux::param("measurement"))
+                    & flux::tag(name.as_str()).exists();
+                format!(
+                    "influxdb.cardinality(bucket: params.bucket, {range}, predicate: (r) => {predicate})
+        |> map(fn: (r) => ({{tag: {name}, _value: r._value}}))",
+                    range = range.args(),
+                    predicate = predicate,
+                    name = Expr::from(name.as_str()),
+                )
+            })
+            .collect();
+        let query = Query::new(format!(
+            "import \"influxdata/influxdb\"\n\n{tables}",
+            tables = union(tables)
+        ))
+        .bind("bucket", bucket)
+        .bind("measurement", measurement);
+
+        let records = self.query_raw(Some(query)).await?;
+        Ok(names
+            .into_iter()
+            .map(|name| {
+                let cardinality = records
+                    .iter()
+                    .find(|record| record.get_str("tag") == Some(name.as_str()))
+                    .and_then(|record| record.get_long("_value"));
+                TagKey { name, cardinality }
+            })
+            .collect())
+    }
+
+    /// Runs a `schema` query, returns the `_value` column of every row
+    async fn exec_schema_query(&self, query: Query) -> Result<Vec<String>, RequestError> {
+        Ok(self
+            .query_raw(Some(query))
+            .await?
+            .iter()
+            .filter_map(|record| record.get_str("_value").map(|s| s.to_owned()))
+            .collect())
+    }
+}
+
+/// The schema helpers of earlier releases, taking Flux time expressions
+impl Client {
+    /// Returns bucket measurements
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket` - The bucket name
+    /// * `start` - Optional start time. Default is `-30d`
+    /// * `stop` - Optional stop time. Default is `now()`
+    #[deprecated(note = "use `Client::measurements` with a `TimeRange`")]
+    pub async fn list_measurements(
+        &self,
+        bucket: &str,
+        start: Option<&str>,
+        stop: Option<&str>,
+    ) -> Result<Vec<String>, RequestError> {
+        let query = Query::new(legacy_schema_query(
+            "measurements",
+            &["bucket"],
+            start,
+            stop,
+        ))
+        .bind("bucket", bucket);
+        self.exec_schema_query(query).await
+    }
+
+    /// List field keys for measurement
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket` - The bucket name
+    /// * `measurement` - The measurement name
+    /// * `start` - Optional start time. Default is `-30d`
+    /// * `stop` - Optional stop time. Default is `now()`
+    #[deprecated(note = "use `Client::measurement_field_keys` with a `TimeRange`")]
+    pub async fn list_measurement_field_keys(
+        &self,
+        bucket: &str,
+        measurement: &str,
+        start: Option<&str>,
+        stop: Option<&str>,
+    ) -> Result<Vec<String>, RequestError> {
+        let query = Query::new(legacy_schema_query(
+            "measurementFieldKeys",
+            &["bucket", "measurement"],
+            start,
+            stop,
+        ))
+        .bind("bucket", bucket)
+        .bind("measurement", measurement);
+        self.exec_schema_query(query).await
+    }
+
+    /// List all tag values for measurement tag
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket` - The bucket name
+    /// * `measurement` - The measurement name
+    /// * `tag` - The tag name
+    /// * `start` - Optional start time. Default is `-30d`
+    /// * `stop` - Optional stop time. Default is `now()`
+    #[deprecated(note = "use `Client::measurement_tag_values` with a `TimeRange`")]
+    pub async fn list_measurement_tag_values(
+        &self,
+        bucket: &str,
+        measurement: &str,
+        tag: &str,
+        start: Option<&str>,
+        stop: Option<&str>,
+    ) -> Result<Vec<String>, RequestError> {
+        let query = Query::new(legacy_schema_query(
+            "measurementTagValues",
+            &["bucket", "measurement", "tag"],
+            start,
+            stop,
+        ))
+        .bind("bucket", bucket)
+        .bind("measurement", measurement)
+        .bind("tag", tag);
+        self.exec_schema_query(query).await
+    }
+
+    /// List all tag keys for measurement
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket` - The bucket name
+    /// * `measurement` - The measurement name
+    /// * `start` - Optional start time. Default is `-30d`
+    /// * `stop` - Optional stop time. Default is `now()`
+    #[deprecated(note = "use `Client::measurement_tag_keys` with a `TimeRange`")]
+    pub async fn list_measurement_tag_keys(
+        &self,
+        bucket: &str,
+        measurement: &str,
+        start: Option<&str>,
+        stop: Option<&str>,
+    ) -> Result<Vec<String>, RequestError> {
+        let query = Query::new(legacy_schema_query(
+            "measurementTagKeys",
+            &["bucket", "measurement"],
+            start,
+            stop,
+        ))
+        .bind("bucket", bucket)
+        .bind("measurement", measurement);
+        self.exec_schema_query(query).await
+    }
+}
+
+/// Union of Flux table streams, or the only one
+fn union(tables: Vec<String>) -> String {
+    if tables.len() == 1 {
+        tables.concat()
+    } else {
+        format!("union(tables: [\n    {}\n])", tables.join(",\n    "))
+    }
+}
+
+/// Builds a call to a `schema` package function, `names` are passed as bound
+/// parameters
+fn schema_query(func: &str, names: &[&str], range: &TimeRange) -> String {
+    let args: Vec<String> = names
+        .iter()
+        .map(|name| format!("{name}: params.{name}"))
+        .collect();
+
+    format!(
+        r#"import "influxdata/influxdb/schema"
+
+schema.{func}({args}, {range})"#,
+        args = args.join(", "),
+        range = range.args()
+    )
+}
+
+/// Builds a call to a `schema` package function. `names` are passed as bound
+/// parameters, `start` and `stop` are Flux time expressions.
+fn legacy_schema_query(
+    func: &str,
+    names: &[&str],
+    start: Option<&str>,
+    stop: Option<&str>,
+) -> String {
+    let mut args: Vec<String> = names
+        .iter()
+        .map(|name| format!("{name}: params.{name}"))
+        .collect();
+    if let Some(start) = start {
+        args.push(format!("start: {start}"));
+    }
+    if let Some(stop) = stop {
+        args.push(format!("stop: {stop}"));
+    }
+    let args = args.join(", ");
+
+    format!(
+        r#"import "influxdata/influxdb/schema"
+
+schema.{func}({args})"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{mock, Matcher};
+
+    #[tokio::test]
+    async fn measurement_tag_values() {
+        let token = "some-token";
+        let org = "some-org";
+        let tag = r#"host") |> drop(columns: ["x"]"#;
+
+        let mock_server = mock("POST", "/api/v2/query")
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .match_query(Matcher::UrlEncoded("org".into(), org.into()))
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "query": "import \"influxdata/influxdb/schema\"\n\nschema.measurementTagValues(bucket: params.bucket, measurement: params.measurement, tag: params.tag, start: -1h, stop: now())",
+                "params": {"bucket": "bucket", "measurement": "cpu", "tag": tag},
+            })))
+            .with_body("#datatype,string,long,string\n#group,false,false,false\n#default,_result,,\n,result,table,_value\n,,0,server01\n")
+            .create();
+
+        let client = Client::new(mockito::server_url(), org, token);
+
+        let result = client
+            .measurement_tag_values(
+                "bucket",
+                "cpu",
+                tag,
+                &TimeRange::since(chrono::Duration::hours(-1)),
+            )
+            .await
+            .unwrap();
+
+        mock_server.assert();
+        assert_eq!(result, vec!["server01".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn measurement_field_keys() {
+        let token = "some-token";
+        let org = "some-org";
+
+        let mock_server = mock("POST", "/api/v2/query")
+            .match_header("Authorization", format!("Token {}", token).as_str())
+            .match_query(Matcher::UrlEncoded("org".into(), org.into()))
+            .match_body(Matcher::AllOf(vec![
+                Matcher::Regex(
+                    r#"r._measurement == params.measurement\)\\n    \|> first\(\)\\n    \|> map\(.*\|> group\(columns: \[\\"_field\\"\]\)\\n    \|> first\(column: \\"_field\\"\)"#
+                        .into(),
+                ),
+                Matcher::PartialJson(serde_json::json!({
+                    "params": {"bucket": "bucket", "measurement": "cpu"},
+                })),
+            ]))
+            .with_body(
+                "#datatype,string,long,string,string
+#group,false,false,false,true
+#default,_result,,,
+,result,table,type,_field
+,,0,long,count
+,,1,double,usage
+",
+            )
+            .create();
+
+        let client = Client::new(mockito::server_url(), org, token);
+        let start = chrono::Utc.ymd(2023, 1, 1).and_hms(0, 0, 0);
+
+        let result = client
+            .measurement_field_keys("bucket", "cpu", &TimeRange::since(start))
+            .await
+            .unwrap();
+
+        mock_server.assert();
+        assert_eq!(
+            result,
+            vec![
+                FieldKey {
+                    name: "count".to_owned(),
+                    data_type: DataType::Long,
+                },
+                FieldKey {
+                    name: "usage".to_owned(),
+                    data_type: DataType::Double,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn measurement_tag_keys() {
+        let token = "some-token";
+        let org = "some-org";
+
+        let keys_mock = mock("POST", "/api/v2/query")
+            .match_query(Matcher::UrlEncoded("org".into(), org.into()))
+            .match_body(Matcher::Regex("schema.measurementTagKeys".into()))
+            .with_body(
+                "#datatype,string,long,string
+#group,false,false,false
+#default,_result,,
+,result,table,_value
+,,0,_measurement
+,,0,host
+,,0,region
+,,0,zone
+",
+            )
+            .create();
+        let cardinality_mock = mock("POST", "/api/v2/query")
+            .match_query(Matcher::UrlEncoded("org".into(), org.into()))
+            .match_body(Matcher::Regex(
+                r#"union\(tables: \[\\n    influxdb.cardinality\(.*exists r.host\)"#.into(),
+            ))
+            .with_body(
+                "#datatype,string,long,long,string
+#group,false,false,false,false
+#default,_result,,,
+,result,table,_value,tag
+,,0,12,host
+,,1,3,region
+",
+            )
+            .create();
+
+        let client = Client::new(mockito::server_url(), org, token);
+
+        let result = client
+            .measurement_tag_keys("bucket", "cpu", &TimeRange::default())
+            .await
+            .unwrap();
+
+        keys_mock.assert();
+        cardinality_mock.assert();
+        assert_eq!(
+            result,
+            vec![
+                TagKey {
+                    name: "host".to_owned(),
+                    cardinality: Some(12),
+                },
+                TagKey {
+                    name: "region".to_owned(),
+                    cardinality: Some(3),
+                },
+                TagKey {
+                    name: "zone".to_owned(),
+                    cardinality: None,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[allow(deprecated)]
+    async fn list_measurement_tag_keys() {
+        let token = "some-token";
+        let org = "some-org";
+
+        let mock_server = mock("POST", "/api/v2/query")
+            .match_query(Matcher::UrlEncoded("org".into(), org.into()))
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "query": "import \"influxdata/influxdb/schema\"\n\nschema.measurementTagKeys(bucket: params.bucket, measurement: params.measurement, start: -1h)",
+                "params": {"bucket": "bucket", "measurement": "cpu"},
+            })))
+            .with_body("#datatype,string,long,string\n#group,false,false,false\n#default,_result,,\n,result,table,_value\n,,0,host\n")
+            .create();
+
+        let client = Client::new(mockito::server_url(), org, token);
+
+        let result = client
+            .list_measurement_tag_keys("bucket", "cpu", Some("-1h"), None)
+            .await
+            .unwrap();
+
+        mock_server.assert();
+        assert_eq!(result, vec!["host".to_string()]);
+    }
+}
@@ -1,14 +1,20 @@
 //! Write API
 
-use crate::models::WriteDataPoint;
-use crate::{Client, Http, RequestError, ReqwestProcessing};
+use crate::models::{Status, WriteDataPoint};
+use crate::spool::{Batch, ReplayReport, Spool};
+use crate::{Client, Http, Io, RequestError, ReqwestProcessing};
 
-use bytes::BufMut;
+use bytes::{BufMut, Bytes};
+use futures::stream::{self, FusedStream};
 use futures::{Stream, StreamExt};
 use reqwest::header::HeaderMap;
 use reqwest::{Body, Method, StatusCode};
+use serde::Deserialize;
 use snafu::ResultExt;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
 use std::io::{self, Write};
+use std::sync::Arc;
 
 impl Client {
     /// Write line protocol data to the specified organization and bucket.
@@ -25,6 +31,12 @@ impl Client {
     }
 
     /// Write line protocol data to the specified organization and bucket.
+    ///
+    /// A body held in memory is split in batches of at most the client's
+    /// `max_batch_size`, and spooled when the server is unavailable if the
+    /// client has a spool. Without a `max_batch_size`, spooled batches stay
+    /// under the segment size of the spool. Streamed bodies are always sent
+    /// as is.
     pub async fn write_line_protocol_with_precision(
         &self,
         org: &str,
@@ -32,6 +44,22 @@ impl Client {
         body: impl Into<Body> + Send,
         precision: TimestampPrecision,
     ) -> Result<(), RequestError> {
+        let body = body.into();
+        if self.spool.is_some() || self.max_batch_size.is_some() {
+            if let Some(bytes) = body.as_bytes() {
+                for body in split_lines(bytes, self.batch_limit()) {
+                    self.write_batch(Batch {
+                        org: org.to_owned(),
+                        bucket: bucket.to_owned(),
+                        precision,
+                        body,
+                    })
+                    .await?;
+                }
+                return Ok(());
+            }
+        }
+
         self.write_line_protocol_with_precision_headers(
             org,
             bucket,
@@ -90,12 +118,52 @@ impl Client {
 
     /// Write a `Stream` of `DataPoint`s to the specified organization and
     /// bucket.
+    ///
+    /// With a `max_batch_size` set on the client, the points are sent in
+    /// several requests that stay under it, or under the segment size of
+    /// the spool if the client only has a spool. When one of them fails,
+    /// the error is returned and the points of the previous ones stay
+    /// written.
     pub async fn write_with_precision(
         &self,
         bucket: &str,
         body: impl Stream<Item = impl WriteDataPoint> + Send + Sync + 'static,
         timestamp_precision: TimestampPrecision,
     ) -> Result<(), RequestError> {
+        if self.spool.is_some() || self.max_batch_size.is_some() {
+            let limit = self.batch_limit();
+            futures::pin_mut!(body);
+            let mut batch = vec![];
+            let mut line = vec![];
+            loop {
+                line.clear();
+                match body.next().await {
+                    Some(point) => point.write_data_point_to(&mut line).context(Io)?,
+                    None => break,
+                }
+                if !batch.is_empty() && batch.len() + line.len() > limit {
+                    self.write_batch(Batch {
+                        org: self.org.clone(),
+                        bucket: bucket.to_owned(),
+                        precision: timestamp_precision,
+                        body: std::mem::take(&mut batch).into(),
+                    })
+                    .await?;
+                }
+                batch.extend_from_slice(&line);
+            }
+            if !batch.is_empty() {
+                self.write_batch(Batch {
+                    org: self.org.clone(),
+                    bucket: bucket.to_owned(),
+                    precision: timestamp_precision,
+                    body: batch.into(),
+                })
+                .await?;
+            }
+            return Ok(());
+        }
+
         let mut buffer = bytes::BytesMut::new();
 
         let body = body.map(move |point| {
@@ -141,6 +209,543 @@ impl Client {
         self.write_line_protocol_with_precision(&self.org, bucket, body, timestamp_precision)
             .await
     }
+
+    /// Write line protocol data, reporting the lines the server rejected
+    /// instead of failing the whole call.
+    ///
+    /// Rejected lines are identified from the error returned by the server.
+    /// When none can be identified, the server error is returned as is. The
+    /// lines are sent in batches of at most the client's `max_batch_size`,
+    /// whose results are combined.
+    pub async fn write_line_protocol_partial(
+        &self,
+        org: &str,
+        bucket: &str,
+        body: &str,
+        precision: TimestampPrecision,
+        mode: PartialWrite,
+    ) -> Result<WriteResult, RequestError> {
+        let lines: Vec<(usize, &str)> = body
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| is_point(line))
+            .map(|(i, line)| (i + 1, line))
+            .collect();
+        let limit = self.batch_limit();
+        let mut result = WriteResult::default();
+
+        let mut batch = vec![];
+        let mut size = 0;
+        for (number, line) in lines {
+            if !batch.is_empty() && size + line.len() + 1 > limit {
+                let lines = std::mem::take(&mut batch);
+                self.write_lines_partial(org, bucket, lines, precision, mode, &mut result)
+                    .await?;
+                size = 0;
+            }
+            size += line.len() + 1;
+            batch.push((number, line));
+        }
+        self.write_lines_partial(org, bucket, batch, precision, mode, &mut result)
+            .await?;
+
+        result.rejected.sort_by_key(|rejected| rejected.line);
+        Ok(result)
+    }
+
+    async fn write_lines_partial(
+        &self,
+        org: &str,
+        bucket: &str,
+        mut lines: Vec<(usize, &str)>,
+        precision: TimestampPrecision,
+        mode: PartialWrite,
+        result: &mut WriteResult,
+    ) -> Result<(), RequestError> {
+        while !lines.is_empty() {
+            let mut body = String::new();
+            for (_, line) in &lines {
+                body.push_str(line);
+                body.push('\n');
+            }
+            let batch = Batch {
+                org: org.to_owned(),
+                bucket: bucket.to_owned(),
+                precision,
+                body: body.into(),
+            };
+
+            let (status, text) = match self.send_batch(&batch).await {
+                Ok(()) => {
+                    result.accepted += lines.len();
+                    break;
+                }
+                Err(RequestError::Http { status, text })
+                    if status == StatusCode::BAD_REQUEST
+                        || status == StatusCode::UNPROCESSABLE_ENTITY =>
+                {
+                    (status, text)
+                }
+                Err(e) => return Err(e),
+            };
+
+            let errors = LineErrors::parse(&text);
+            let mut rejected = HashSet::new();
+            for (index, reason) in errors.lines {
+                if let Some((line, text)) = index.checked_sub(1).and_then(|i| lines.get(i)) {
+                    if rejected.insert(*line) {
+                        result.rejected.push(RejectedLine {
+                            line: *line,
+                            text: (*text).to_owned(),
+                            reason,
+                        });
+                    }
+                }
+            }
+            if rejected.is_empty() {
+                return Http { status, text }.fail();
+            }
+            lines.retain(|(line, _)| !rejected.contains(line));
+
+            if errors.partial {
+                result.accepted += lines.len();
+                break;
+            }
+            if mode == PartialWrite::Report {
+                result.skipped += lines.len();
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a `Stream` of `DataPoint`s, reporting the points the server
+    /// rejected instead of failing the whole call. See
+    /// `write_line_protocol_partial`, lines are numbered after the points.
+    pub async fn write_partial(
+        &self,
+        bucket: &str,
+        body: impl Stream<Item = impl WriteDataPoint> + Send + Sync + 'static,
+        precision: TimestampPrecision,
+        mode: PartialWrite,
+    ) -> Result<WriteResult, RequestError> {
+        let mut buffer = vec![];
+        for point in body.collect::<Vec<_>>().await {
+            point.write_data_point_to(&mut buffer).context(Io)?;
+        }
+        let body = String::from_utf8_lossy(&buffer);
+
+        self.write_line_protocol_partial(&self.org, bucket, &body, precision, mode)
+            .await
+    }
+
+    /// Write a `Stream` of `(org, bucket, point)` items, each point to its
+    /// own organization and bucket.
+    ///
+    /// The points are grouped in one batch per destination, sent once it
+    /// reaches the client's `max_batch_size` and when the stream ends, with
+    /// at most `concurrency` requests in flight. Without a `max_batch_size`,
+    /// every batch is held in memory until the stream ends.
+    ///
+    /// The batches of one destination may be sent concurrently, so the
+    /// order in which its points are written is not kept. When a request
+    /// fails, the error is returned and the other requests are cancelled;
+    /// the batches sent before stay written.
+    pub async fn write_routed<O, B, P>(
+        &self,
+        body: impl Stream<Item = (O, B, P)> + Send,
+        precision: TimestampPrecision,
+        concurrency: usize,
+    ) -> Result<(), RequestError>
+    where
+        O: AsRef<str> + Send,
+        B: AsRef<str> + Send,
+        P: WriteDataPoint + Send,
+    {
+        futures::pin_mut!(body);
+        let router = Router {
+            body: body.fuse(),
+            batches: HashMap::new(),
+            limit: self.batch_limit(),
+            precision,
+        };
+        let batches = stream::unfold(router, |mut router| async move {
+            router.next_batch().await.map(|batch| (batch, router))
+        });
+
+        let writes = batches
+            .map(|batch| async move { self.write_batch(batch?).await })
+            .buffer_unordered(concurrency.max(1));
+        futures::pin_mut!(writes);
+        while let Some(result) = writes.next().await {
+            result?;
+        }
+        Ok(())
+    }
+
+    /// Write a `Stream` of points, each one to the `(org, bucket)` returned
+    /// by `route`. See `write_routed`.
+    pub async fn write_routed_by<O, B, P>(
+        &self,
+        body: impl Stream<Item = P> + Send,
+        mut route: impl FnMut(&P) -> (O, B) + Send,
+        precision: TimestampPrecision,
+        concurrency: usize,
+    ) -> Result<(), RequestError>
+    where
+        O: AsRef<str> + Send,
+        B: AsRef<str> + Send,
+        P: WriteDataPoint + Send,
+    {
+        let body = body.map(move |point| {
+            let (org, bucket) = route(&point);
+            (org, bucket, point)
+        });
+        self.write_routed(body, precision, concurrency).await
+    }
+
+    /// Replay the writes spooled while the server was unavailable, oldest
+    /// first, once `health()` reports it as passing. Returns the number of
+    /// batches written, and of the batches dropped because the server
+    /// rejected them as invalid.
+    ///
+    /// On any other error, the batches not written yet stay in the spool
+    /// and the error is returned. Replays also happen on every write while
+    /// the spool is not empty.
+    pub async fn replay_spool(&self) -> Result<ReplayReport, RequestError> {
+        let mut report = ReplayReport::default();
+        let spool = match &self.spool {
+            Some(spool) => spool,
+            None => return Ok(report),
+        };
+        let _replay = match spool.start_replay() {
+            Some(replay) => replay,
+            None => return Ok(report),
+        };
+        if spool.is_empty() || self.health().await?.status != Status::Pass {
+            return Ok(report);
+        }
+
+        while let Some((id, batches)) = spool.oldest().await.context(Io)? {
+            for (i, batch) in batches.iter().enumerate() {
+                match self.send_batch(batch).await {
+                    Ok(()) => report.written += 1,
+                    Err(RequestError::Http { status, .. }) if is_rejected(status) => {
+                        report.dropped += 1
+                    }
+                    Err(e) => {
+                        spool.retain(id, batches[i..].to_vec()).await.context(Io)?;
+                        return Err(e);
+                    }
+                }
+            }
+            spool.retain(id, vec![]).await.context(Io)?;
+        }
+        Ok(report)
+    }
+
+    /// Size of the spooled writes on disk in bytes, `None` without a spool
+    pub fn spool_size(&self) -> Option<u64> {
+        self.spool.as_ref().map(|spool| spool.size())
+    }
+
+    /// Maximum size of a batch, spooled batches default to the size of a
+    /// segment so a large write is not buffered and spooled as one record
+    fn batch_limit(&self) -> usize {
+        self.max_batch_size
+            .or_else(|| {
+                let segment_size = self.spool.as_ref()?.segment_size();
+                Some(usize::try_from(segment_size).unwrap_or(usize::MAX))
+            })
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Send a batch through the spool when the client has one
+    async fn write_batch(&self, batch: Batch) -> Result<(), RequestError> {
+        match &self.spool {
+            Some(spool) => self.write_spooled(spool, batch).await,
+            None => self.send_batch(&batch).await,
+        }
+    }
+
+    /// Send a batch, spooling it when the server is unavailable. Pending
+    /// batches go first so the order of the writes is kept.
+    async fn write_spooled(&self, spool: &Arc<Spool>, batch: Batch) -> Result<(), RequestError> {
+        if !spool.is_empty() {
+            spool.append(&batch).await.context(Io)?;
+            // The batch is safe on disk, the replay is retried on the next write
+            let _ = self.replay_spool().await;
+            return Ok(());
+        }
+
+        match self.send_batch(&batch).await {
+            Err(e) if is_unavailable(&e) => spool.append(&batch).await.context(Io),
+            result => result,
+        }
+    }
+
+    async fn send_batch(&self, batch: &Batch) -> Result<(), RequestError> {
+        #[cfg(feature = "gzip")]
+        {
+            use crate::Compression;
+            use async_compression::tokio::bufread::GzipEncoder;
+            use async_compression::Level;
+            use reqwest::header::HeaderValue;
+            use tokio::io::AsyncReadExt;
+
+            if let Compression::Gzip = self.compression {
+                let mut body = vec![];
+                GzipEncoder::with_quality(&batch.body[..], Level::Best)
+                    .read_to_end(&mut body)
+                    .await
+                    .context(Io)?;
+
+                let mut headers = HeaderMap::new();
+                headers.insert("Content-Encoding", HeaderValue::from_static("gzip"));
+
+                return self
+                    .write_line_protocol_with_precision_headers(
+                        &batch.org,
+                        &batch.bucket,
+                        body,
+                        batch.precision,
+                        headers,
+                    )
+                    .await;
+            }
+        }
+
+        self.write_line_protocol_with_precision_headers(
+            &batch.org,
+            &batch.bucket,
+            batch.body.clone(),
+            batch.precision,
+            HeaderMap::new(),
+        )
+        .await
+    }
+}
+
+/// Groups the points of `write_routed` in batches per organization and
+/// bucket
+struct Router<S> {
+    body: S,
+    /// Lines pending by organization, then by bucket
+    batches: HashMap<String, HashMap<String, Vec<u8>>>,
+    limit: usize,
+    precision: TimestampPrecision,
+}
+
+impl<S, O, B, P> Router<S>
+where
+    S: Stream<Item = (O, B, P)> + FusedStream + Unpin,
+    O: AsRef<str>,
+    B: AsRef<str>,
+    P: WriteDataPoint,
+{
+    /// The next batch to send: one that would go over the limit with its
+    /// next point, then, once the points run out, every pending one
+    async fn next_batch(&mut self) -> Option<Result<Batch, RequestError>> {
+        let mut line = vec![];
+        while let Some((org, bucket, point)) = self.body.next().await {
+            line.clear();
+            if let Err(e) = point.write_data_point_to(&mut line).context(Io) {
+                return Some(Err(e));
+            }
+
+            let (org, bucket) = (org.as_ref(), bucket.as_ref());
+            let limit = self.limit;
+            let pending = self.pending(org, bucket);
+            let full = if !pending.is_empty() && pending.len() + line.len() > limit {
+                Some(std::mem::take(pending))
+            } else {
+                None
+            };
+            pending.extend_from_slice(&line);
+
+            if let Some(body) = full {
+                return Some(Ok(self.batch(org.to_owned(), bucket.to_owned(), body)));
+            }
+        }
+
+        let org = self.batches.keys().next()?.clone();
+        let buckets = self.batches.get_mut(&org)?;
+        let bucket = buckets.keys().next()?.clone();
+        let body = buckets.remove(&bucket)?;
+        if buckets.is_empty() {
+            self.batches.remove(&org);
+        }
+        Some(Ok(self.batch(org, bucket, body)))
+    }
+
+    /// The lines pending for a destination, only allocating its keys the
+    /// first time
+    fn pending(&mut self, org: &str, bucket: &str) -> &mut Vec<u8> {
+        if !self.batches.contains_key(org) {
+            self.batches.insert(org.to_owned(), HashMap::new());
+        }
+        let buckets = self.batches.get_mut(org).expect("inserted above");
+        if !buckets.contains_key(bucket) {
+            buckets.insert(bucket.to_owned(), vec![]);
+        }
+        buckets.get_mut(bucket).expect("inserted above")
+    }
+
+    fn batch(&self, org: String, bucket: String, body: Vec<u8>) -> Batch {
+        Batch {
+            org,
+            bucket,
+            precision: self.precision,
+            body: body.into(),
+        }
+    }
+}
+
+/// Whether the write failed because the server could not take it for now
+fn is_unavailable(error: &RequestError) -> bool {
+    match error {
+        RequestError::ReqwestProcessing { .. } => true,
+        RequestError::Http { status, .. } => {
+            status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+        }
+        _ => false,
+    }
+}
+
+/// Whether the server refused the data itself, so that sending it again is
+/// pointless
+fn is_rejected(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_REQUEST | StatusCode::PAYLOAD_TOO_LARGE | StatusCode::UNPROCESSABLE_ENTITY
+    )
+}
+
+/// How `write_line_protocol_partial` handles a batch with rejected lines
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum PartialWrite {
+    /// Report the rejected lines, the other lines refused along with them
+    /// are counted as skipped
+    #[default]
+    Report,
+    /// Leave the rejected lines out and send the remaining ones again
+    Resend,
+}
+
+/// Outcome of a write tolerating rejected lines
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WriteResult {
+    /// Number of lines written
+    pub accepted: usize,
+    /// Lines refused by the server, in order
+    pub rejected: Vec<RejectedLine>,
+    /// Number of valid lines not written because the server refused the
+    /// batch they were part of
+    pub skipped: usize,
+}
+
+impl WriteResult {
+    /// Whether every line was written
+    pub fn is_complete(&self) -> bool {
+        self.rejected.is_empty() && self.skipped == 0
+    }
+}
+
+/// Line refused by the server
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RejectedLine {
+    /// Line number in the body, starting at 1
+    pub line: usize,
+    /// Content of the line
+    pub text: String,
+    /// Reason given by the server
+    pub reason: String,
+}
+
+/// Line errors found in the error response of a write
+#[derive(Debug, Default)]
+struct LineErrors {
+    /// Line numbers in the request body, with the reason they were rejected
+    lines: Vec<(usize, String)>,
+    /// Whether the other lines were written
+    partial: bool,
+}
+
+impl LineErrors {
+    /// Parses the error of a write. InfluxDB 2 lists the lines as
+    /// `line N: reason` in the message, or gives the first one in `line`.
+    /// InfluxDB 3 details each of them in `data`.
+    fn parse(text: &str) -> Self {
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(alias = "error")]
+            message: Option<String>,
+            line: Option<usize>,
+            #[serde(default)]
+            data: Vec<Detail>,
+        }
+
+        #[derive(Deserialize)]
+        struct Detail {
+            error_message: String,
+            line_number: usize,
+        }
+
+        let response: Response = match serde_json::from_str(text) {
+            Ok(response) => response,
+            Err(_) => return Self::default(),
+        };
+        let message = response.message.unwrap_or_default();
+        let mut lines: Vec<_> = response
+            .data
+            .into_iter()
+            .map(|detail| (detail.line_number, detail.error_message))
+            .collect();
+        if lines.is_empty() {
+            lines = message
+                .lines()
+                .filter_map(|line| {
+                    let (number, reason) = line.trim().strip_prefix("line ")?.split_once(':')?;
+                    Some((number.trim().parse().ok()?, reason.trim().to_owned()))
+                })
+                .collect();
+        }
+        if lines.is_empty() {
+            if let Some(line) = response.line {
+                lines.push((line, message.clone()));
+            }
+        }
+
+        Self {
+            lines,
+            partial: message.starts_with("partial write"),
+        }
+    }
+}
+
+/// Splits line protocol in batches of at most `limit` bytes, unless a
+/// single line is longer
+fn split_lines(body: &[u8], limit: usize) -> Vec<Bytes> {
+    let body = Bytes::copy_from_slice(body);
+    let mut batches = vec![];
+    let (mut start, mut end) = (0, 0);
+    for line in body.split_inclusive(|b| *b == b'\n') {
+        if end > start && end - start + line.len() > limit {
+            batches.push(body.slice(start..end));
+            start = end;
+        }
+        end += line.len();
+    }
+    if end > start {
+        batches.push(body.slice(start..end));
+    }
+    batches
+}
+
+/// Whether a line of line protocol holds a point, not a comment or blank
+fn is_point(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && !line.starts_with('#')
 }
 
 /// Possible timestamp precisions.
@@ -293,4 +898,360 @@ cpu,host=server01 usage=0.5 1671095854
             assert!(write_with_status(status).await.is_err());
         }
     }
+
+    #[tokio::test]
+    async fn spooling_writes() {
+        let org = "some-org";
+        let bucket = "spooled-bucket";
+        let token = "some-token";
+        let path = format!("/api/v2/write?bucket={}&org={}&precision=ns", bucket, org);
+        let dir = test_helpers::tmp_dir().unwrap();
+
+        let client = crate::ClientBuilder::new(mockito::server_url(), org, token)
+            .spool(crate::spool::SpoolOptions::new(dir.path()))
+            .build()
+            .unwrap();
+        let point = || {
+            DataPoint::builder("cpu")
+                .field("usage", 0.5)
+                .timestamp(1)
+                .build()
+                .unwrap()
+        };
+
+        let unavailable = mock("POST", path.as_str()).with_status(503).create();
+        let result = client.write(bucket, stream::iter(vec![point()])).await;
+        unavailable.assert();
+        assert!(result.is_ok());
+        assert!(client.spool_size().unwrap() > 0);
+
+        let health = mock("GET", "/health")
+            .with_status(200)
+            .with_body(r#"{"name":"influxdb","status":"pass"}"#)
+            .create();
+        let written = mock("POST", path.as_str())
+            .match_body("cpu usage=0.5 1\n")
+            .with_status(204)
+            .expect(1)
+            .create();
+        let replayed = client.replay_spool().await;
+        health.assert();
+        written.assert();
+        assert_eq!(
+            replayed.unwrap(),
+            ReplayReport {
+                written: 1,
+                dropped: 0
+            }
+        );
+        assert_eq!(client.spool_size(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn replaying_drops_invalid_writes() {
+        let org = "some-org";
+        let bucket = "dropped-bucket";
+        let path = format!("/api/v2/write?bucket={}&org={}&precision=ns", bucket, org);
+        let dir = test_helpers::tmp_dir().unwrap();
+
+        // Without a `max_batch_size`, batches stay under the segment size
+        let client = crate::ClientBuilder::new(mockito::server_url(), org, "some-token")
+            .spool(crate::spool::SpoolOptions::new(dir.path()).segment_size(1))
+            .build()
+            .unwrap();
+
+        let unavailable = mock("POST", path.as_str())
+            .with_status(503)
+            .expect_at_least(1)
+            .create();
+        let result = client
+            .write_line_protocol(org, bucket, "cpu usage=0.5 1\ncpu usage=0.6 2\n")
+            .await;
+        unavailable.assert();
+        assert!(result.is_ok());
+
+        let health = mock("GET", "/health")
+            .with_status(200)
+            .with_body(r#"{"name":"influxdb","status":"pass"}"#)
+            .create();
+        let rejected = mock("POST", path.as_str())
+            .with_status(400)
+            .expect(2)
+            .create();
+        let replayed = client.replay_spool().await;
+        health.assert();
+        rejected.assert();
+        assert_eq!(
+            replayed.unwrap(),
+            ReplayReport {
+                written: 0,
+                dropped: 2
+            }
+        );
+        assert_eq!(client.spool_size(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn spooling_keeps_invalid_writes_out() {
+        let org = "some-org";
+        let bucket = "invalid-bucket";
+        let dir = test_helpers::tmp_dir().unwrap();
+
+        let mock_server = mock(
+            "POST",
+            format!("/api/v2/write?bucket={}&org={}&precision=ns", bucket, org).as_str(),
+        )
+        .with_status(400)
+        .create();
+        let client = crate::ClientBuilder::new(mockito::server_url(), org, "some-token")
+            .spool(crate::spool::SpoolOptions::new(dir.path()))
+            .build()
+            .unwrap();
+
+        let result = client
+            .write_line_protocol(org, bucket, "cpu usage=0.5 1\n")
+            .await;
+        mock_server.assert();
+        assert!(result.is_err());
+        assert_eq!(client.spool_size(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn writing_partially() {
+        let org = "some-org";
+        let bucket = "partial-bucket";
+        let path = format!("/api/v2/write?bucket={}&org={}&precision=ns", bucket, org);
+        let body = "\
+cpu usage=0.5 1
+cpu usage= 2
+
+# comment
+cpu usage=0.7 3
+";
+
+        let rejected = mock("POST", path.as_str())
+            .match_body("cpu usage=0.5 1\ncpu usage= 2\ncpu usage=0.7 3\n")
+            .with_status(400)
+            .with_body(
+                r#"{"code":"invalid","message":"failed to parse line protocol: errors encountered on line(s):\nline 2: invalid field format","line":2}"#,
+            )
+            .expect(2)
+            .create();
+        let written = mock("POST", path.as_str())
+            .match_body("cpu usage=0.5 1\ncpu usage=0.7 3\n")
+            .with_status(204)
+            .create();
+        let client = Client::new(mockito::server_url(), org, "some-token");
+
+        let rejected_line = RejectedLine {
+            line: 2,
+            text: "cpu usage= 2".to_owned(),
+            reason: "invalid field format".to_owned(),
+        };
+        let report = client
+            .write_line_protocol_partial(
+                org,
+                bucket,
+                body,
+                TimestampPrecision::Nanoseconds,
+                PartialWrite::Report,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            WriteResult {
+                accepted: 0,
+                rejected: vec![rejected_line.clone()],
+                skipped: 2,
+            }
+        );
+
+        let resent = client
+            .write_line_protocol_partial(
+                org,
+                bucket,
+                body,
+                TimestampPrecision::Nanoseconds,
+                PartialWrite::Resend,
+            )
+            .await
+            .unwrap();
+        rejected.assert();
+        written.assert();
+        assert_eq!(
+            resent,
+            WriteResult {
+                accepted: 2,
+                rejected: vec![rejected_line],
+                skipped: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn parsing_line_errors() {
+        let errors = LineErrors::parse(
+            r#"{
+                "error": "partial write of line protocol occurred",
+                "data": [
+                    {"original_line": "cpu", "line_number": 3, "error_message": "No fields were provided"}
+                ]
+            }"#,
+        );
+        assert!(errors.partial);
+        assert_eq!(
+            errors.lines,
+            vec![(3, "No fields were provided".to_owned())]
+        );
+
+        let errors =
+            LineErrors::parse(r#"{"code":"invalid","message":"unable to parse","line":4}"#);
+        assert!(!errors.partial);
+        assert_eq!(errors.lines, vec![(4, "unable to parse".to_owned())]);
+
+        assert!(LineErrors::parse("Bad Gateway").lines.is_empty());
+    }
+
+    #[tokio::test]
+    async fn writing_in_batches() {
+        let org = "some-org";
+        let bucket = "batched-bucket";
+        let path = format!("/api/v2/write?bucket={}&org={}&precision=ns", bucket, org);
+
+        let first = mock("POST", path.as_str())
+            .match_body("cpu usage=1i 1\ncpu usage=2i 2\n")
+            .with_status(204)
+            .create();
+        let second = mock("POST", path.as_str())
+            .match_body("cpu usage=3i 3\n")
+            .with_status(204)
+            .create();
+        let client = crate::ClientBuilder::new(mockito::server_url(), org, "some-token")
+            .max_batch_size(30)
+            .build()
+            .unwrap();
+
+        let points: Vec<_> = (1..=3)
+            .map(|i| {
+                DataPoint::builder("cpu")
+                    .field("usage", i)
+                    .timestamp(i)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        let result = client.write(bucket, stream::iter(points)).await;
+        first.assert();
+        second.assert();
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn writing_routed() {
+        let path = |org: &str, bucket: &str| {
+            format!("/api/v2/write?bucket={}&org={}&precision=ns", bucket, org)
+        };
+
+        let first = mock("POST", path("tenant-a", "metrics").as_str())
+            .match_body("cpu usage=1i 1\ncpu usage=2i 2\n")
+            .with_status(204)
+            .create();
+        let second = mock("POST", path("tenant-a", "metrics").as_str())
+            .match_body("cpu usage=4i 4\n")
+            .with_status(204)
+            .create();
+        let other_bucket = mock("POST", path("tenant-a", "events").as_str())
+            .match_body("cpu usage=3i 3\n")
+            .with_status(204)
+            .create();
+        let other_org = mock("POST", path("tenant-b", "metrics").as_str())
+            .match_body("cpu usage=5i 5\n")
+            .with_status(204)
+            .create();
+        let client = crate::ClientBuilder::new(mockito::server_url(), "some-org", "some-token")
+            .max_batch_size(30)
+            .build()
+            .unwrap();
+
+        let point = |i| {
+            DataPoint::builder("cpu")
+                .field("usage", i)
+                .timestamp(i)
+                .build()
+                .unwrap()
+        };
+        let points = vec![
+            ("tenant-a", "metrics", point(1)),
+            ("tenant-a", "metrics", point(2)),
+            ("tenant-a", "events", point(3)),
+            ("tenant-a", "metrics", point(4)),
+            ("tenant-b", "metrics", point(5)),
+        ];
+        let result = client
+            .write_routed(stream::iter(points), TimestampPrecision::Nanoseconds, 2)
+            .await;
+        first.assert();
+        second.assert();
+        other_bucket.assert();
+        other_org.assert();
+        assert!(result.is_ok());
+
+        let cpu = mock("POST", path("tenant-c", "cpu").as_str())
+            .match_body("cpu usage=6i 6\n")
+            .with_status(204)
+            .create();
+        let mem = mock("POST", path("tenant-c", "mem").as_str())
+            .match_body("mem usage=7i 7\n")
+            .with_status(204)
+            .create();
+        let mut routed_points = 0;
+        let points = vec![
+            DataPoint::builder("cpu").field("usage", 6).timestamp(6),
+            DataPoint::builder("mem").field("usage", 7).timestamp(7),
+        ];
+        let result = client
+            .write_routed_by(
+                stream::iter(points.into_iter().map(|p| p.build().unwrap())),
+                |_| {
+                    routed_points += 1;
+                    let bucket = if routed_points == 1 { "cpu" } else { "mem" };
+                    ("tenant-c".to_owned(), bucket)
+                },
+                TimestampPrecision::Nanoseconds,
+                1,
+            )
+            .await;
+        cpu.assert();
+        mem.assert();
+        assert!(result.is_ok());
+
+        let failing = mock("POST", path("tenant-d", "metrics").as_str())
+            .with_status(400)
+            .create();
+        let points = vec![("tenant-d", "metrics", point(8))];
+        let result = client
+            .write_routed(stream::iter(points), TimestampPrecision::Nanoseconds, 1)
+            .await;
+        failing.assert();
+        assert!(matches!(result, Err(RequestError::Http { .. })));
+    }
+
+    #[test]
+    fn splitting_lines() {
+        let body = b"cpu usage=1 1\ncpu usage=2 2\ncpu,host=long usage=3 3\ncpu usage=4 4";
+
+        let batches = split_lines(body, 20);
+        assert_eq!(
+            batches,
+            vec![
+                Bytes::from_static(b"cpu usage=1 1\n"),
+                Bytes::from_static(b"cpu usage=2 2\n"),
+                Bytes::from_static(b"cpu,host=long usage=3 3\n"),
+                Bytes::from_static(b"cpu usage=4 4"),
+            ]
+        );
+        assert_eq!(split_lines(body, usize::MAX).len(), 1);
+        assert!(split_lines(b"", 20).is_empty());
+    }
 }
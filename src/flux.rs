@@ -0,0 +1,537 @@
+//! Flux
+//!
+//! Typed builder for Flux queries. Values are rendered as properly escaped
+//! Flux literals, or referenced as bound `params`, so user input never ends
+//! up in the query as raw source.
+//!
+//! ```
+//! use influxdb2::flux::{self, from, measurement, tag, Aggregate};
+//! use influxdb2::models::Query;
+//!
+//! let query: Query = from("bucket")
+//!     .range(chrono::Duration::hours(-1), flux::now())
+//!     .filter(measurement().eq("cpu") & tag("host").eq(flux::param("host")))
+//!     .aggregate_window(chrono::Duration::minutes(5), Aggregate::Mean)
+//!     .pivot_fields()
+//!     .yield_()
+//!     .bind("host", "server01")
+//!     .into();
+//! ```
+
+use std::fmt::{self, Display, Formatter, Write};
+use std::ops::{BitAnd, BitOr, Not};
+
+use chrono::{DateTime, TimeZone};
+
+use crate::models::query::format_duration;
+use crate::models::{Query, QueryParam};
+
+/// Start a query reading from `bucket`
+pub fn from(bucket: impl Into<Expr>) -> Pipeline {
+    Pipeline {
+        stages: vec![format!("from(bucket: {})", bucket.into())],
+        params: Vec::new(),
+    }
+}
+
+/// Reference the `_measurement` column
+pub fn measurement() -> Column {
+    col("_measurement")
+}
+
+/// Reference the `_field` column
+pub fn field() -> Column {
+    col("_field")
+}
+
+/// Reference the `_value` column
+pub fn value() -> Column {
+    col("_value")
+}
+
+/// Reference a tag column
+pub fn tag(name: impl Into<String>) -> Column {
+    col(name)
+}
+
+/// Reference any column
+pub fn col(name: impl Into<String>) -> Column {
+    Column { name: name.into() }
+}
+
+/// Reference the bound parameter `params.<name>`
+pub fn param(name: impl Into<String>) -> Expr {
+    Expr::Param {
+        name: name.into(),
+        convert: None,
+    }
+}
+
+/// Reference the bound parameter `params.<name>` as a time, for parameters
+/// bound to a time value
+pub fn param_time(name: impl Into<String>) -> Expr {
+    Expr::Param {
+        name: name.into(),
+        convert: Some("time"),
+    }
+}
+
+/// Reference the bound parameter `params.<name>` as a duration, for
+/// parameters bound to a duration value
+pub fn param_duration(name: impl Into<String>) -> Expr {
+    Expr::Param {
+        name: name.into(),
+        convert: Some("duration"),
+    }
+}
+
+/// The current time, `now()`
+pub fn now() -> Expr {
+    Expr::Now
+}
+
+/// A time literal
+pub fn time<Tz: TimeZone>(value: DateTime<Tz>) -> Expr {
+    Expr::from(value)
+}
+
+/// A Flux query under construction
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pipeline {
+    stages: Vec<String>,
+    params: Vec<(String, QueryParam)>,
+}
+
+impl Pipeline {
+    /// Filter rows by time, `start` inclusive and `stop` exclusive
+    pub fn range(self, start: impl Into<Expr>, stop: impl Into<Expr>) -> Self {
+        self.pipe(format!(
+            "range(start: {}, stop: {})",
+            start.into(),
+            stop.into()
+        ))
+    }
+
+    /// Filter rows by time from `start` until now
+    pub fn range_from(self, start: impl Into<Expr>) -> Self {
+        self.pipe(format!("range(start: {})", start.into()))
+    }
+
+    /// Keep rows matching `predicate`
+    pub fn filter(self, predicate: Predicate) -> Self {
+        self.pipe(format!("filter(fn: (r) => {})", predicate))
+    }
+
+    /// Downsample into windows of `every`, aggregating each with `aggregate`
+    pub fn aggregate_window(self, every: impl Into<Expr>, aggregate: Aggregate) -> Self {
+        self.pipe(format!(
+            "aggregateWindow(every: {}, fn: {})",
+            every.into(),
+            aggregate
+        ))
+    }
+
+    /// Turn fields into columns, one row per timestamp
+    pub fn pivot_fields(self) -> Self {
+        self.pipe(
+            r#"pivot(rowKey: ["_time"], columnKey: ["_field"], valueColumn: "_value")"#.to_owned(),
+        )
+    }
+
+    /// Limit each table to `n` rows
+    pub fn limit(self, n: u64) -> Self {
+        self.pipe(format!("limit(n: {})", n))
+    }
+
+    /// Yield the result as `_result`
+    pub fn yield_(self) -> Self {
+        self.pipe("yield()".to_owned())
+    }
+
+    /// Yield the result under `name`
+    pub fn yield_as(self, name: &str) -> Self {
+        self.pipe(format!("yield(name: {})", Expr::from(name)))
+    }
+
+    /// Bind a value to `params.<name>`, see [`param`]
+    pub fn bind(mut self, name: impl Into<String>, value: impl Into<QueryParam>) -> Self {
+        self.params.push((name.into(), value.into()));
+        self
+    }
+
+    /// Render to a [`Query`] with all bound parameters
+    pub fn into_query(self) -> Query {
+        let mut query = Query::new(self.to_string());
+        query.params.extend(self.params);
+        query
+    }
+
+    fn pipe(mut self, stage: String) -> Self {
+        self.stages.push(stage);
+        self
+    }
+}
+
+impl Display for Pipeline {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.stages.join("\n    |> "))
+    }
+}
+
+impl From<Pipeline> for Query {
+    fn from(pipeline: Pipeline) -> Self {
+        pipeline.into_query()
+    }
+}
+
+/// Aggregate function used by [`Pipeline::aggregate_window`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Aggregate {
+    /// Number of rows
+    Count,
+    /// First row
+    First,
+    /// Last row
+    Last,
+    /// Largest value
+    Max,
+    /// Average value
+    Mean,
+    /// Median value
+    Median,
+    /// Smallest value
+    Min,
+    /// Difference between the largest and smallest value
+    Spread,
+    /// Standard deviation
+    Stddev,
+    /// Sum of all values
+    Sum,
+}
+
+impl Display for Aggregate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Count => "count",
+            Self::First => "first",
+            Self::Last => "last",
+            Self::Max => "max",
+            Self::Mean => "mean",
+            Self::Median => "median",
+            Self::Min => "min",
+            Self::Spread => "spread",
+            Self::Stddev => "stddev",
+            Self::Sum => "sum",
+        })
+    }
+}
+
+/// A value in a Flux query: a literal, a bound parameter or `now()`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    /// Literal value
+    Literal(QueryParam),
+    /// Bound parameter, optionally converted with a Flux function
+    Param {
+        /// Parameter name
+        name: String,
+        /// Conversion function applied to the parameter
+        convert: Option<&'static str>,
+    },
+    /// The current time
+    Now,
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Literal(value) => write_literal(f, value),
+            Self::Param {
+                name,
+                convert: Some(convert),
+            } => write!(f, "{}(v: {})", convert, Member("params", name)),
+            Self::Param {
+                name,
+                convert: None,
+            } => write!(f, "{}", Member("params", name)),
+            Self::Now => f.write_str("now()"),
+        }
+    }
+}
+
+impl<T: Into<QueryParam>> From<T> for Expr {
+    fn from(value: T) -> Self {
+        Self::Literal(value.into())
+    }
+}
+
+/// A column of the row passed to [`Pipeline::filter`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Column {
+    name: String,
+}
+
+impl Column {
+    /// `r.<column> == value`
+    pub fn eq(self, value: impl Into<Expr>) -> Predicate {
+        self.compare("==", value)
+    }
+
+    /// `r.<column> != value`
+    pub fn ne(self, value: impl Into<Expr>) -> Predicate {
+        self.compare("!=", value)
+    }
+
+    /// `r.<column> < value`
+    pub fn lt(self, value: impl Into<Expr>) -> Predicate {
+        self.compare("<", value)
+    }
+
+    /// `r.<column> <= value`
+    pub fn le(self, value: impl Into<Expr>) -> Predicate {
+        self.compare("<=", value)
+    }
+
+    /// `r.<column> > value`
+    pub fn gt(self, value: impl Into<Expr>) -> Predicate {
+        self.compare(">", value)
+    }
+
+    /// `r.<column> >= value`
+    pub fn ge(self, value: impl Into<Expr>) -> Predicate {
+        self.compare(">=", value)
+    }
+
+    /// `exists r.<column>`
+    pub fn exists(self) -> Predicate {
+        Predicate::Exists(self)
+    }
+
+    fn compare(self, operator: &'static str, value: impl Into<Expr>) -> Predicate {
+        Predicate::Compare {
+            column: self,
+            operator,
+            value: value.into(),
+        }
+    }
+}
+
+/// Row predicate used by [`Pipeline::filter`], combined with `&`, `|` and `!`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Predicate {
+    /// Compare a column with a value
+    Compare {
+        /// Column
+        column: Column,
+        /// Comparison operator
+        operator: &'static str,
+        /// Value compared against
+        value: Expr,
+    },
+    /// Column is present
+    Exists(Column),
+    /// Both predicates match
+    And(Box<Self>, Box<Self>),
+    /// Either predicate matches
+    Or(Box<Self>, Box<Self>),
+    /// Predicate does not match
+    Not(Box<Self>),
+}
+
+impl Predicate {
+    /// Binding strength of the operator, `not` binds looser than comparisons
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Or(..) => 1,
+            Self::And(..) => 2,
+            Self::Not(..) => 3,
+            Self::Compare { .. } | Self::Exists(..) => 4,
+        }
+    }
+
+    fn write_operand(&self, f: &mut Formatter<'_>, parent: u8) -> fmt::Result {
+        if self.precedence() < parent {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl Display for Predicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Compare {
+                column,
+                operator,
+                value,
+            } => write!(f, "{} {} {}", Member("r", &column.name), operator, value),
+            Self::Exists(column) => write!(f, "exists {}", Member("r", &column.name)),
+            Self::And(left, right) => {
+                left.write_operand(f, 2)?;
+                f.write_str(" and ")?;
+                right.write_operand(f, 2)
+            }
+            Self::Or(left, right) => {
+                left.write_operand(f, 1)?;
+                f.write_str(" or ")?;
+                right.write_operand(f, 1)
+            }
+            Self::Not(inner) => {
+                f.write_str("not ")?;
+                inner.write_operand(f, 3)
+            }
+        }
+    }
+}
+
+impl BitAnd for Predicate {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self::And(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl BitOr for Predicate {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self::Or(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl Not for Predicate {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::Not(Box::new(self))
+    }
+}
+
+/// Member access, `object.property` or `object["property"]` when the
+/// property is not a valid identifier
+struct Member<'a>(&'a str, &'a str);
+
+impl Display for Member<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut chars = self.1.chars();
+        let is_identifier = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        if is_identifier {
+            write!(f, "{}.{}", self.0, self.1)
+        } else {
+            write!(f, "{}[", self.0)?;
+            write_string(f, self.1)?;
+            f.write_char(']')
+        }
+    }
+}
+
+fn write_literal(f: &mut Formatter<'_>, value: &QueryParam) -> fmt::Result {
+    match value {
+        QueryParam::String(v) => write_string(f, v),
+        QueryParam::Int(v) => write!(f, "{}", v),
+        QueryParam::UInt(v) => write!(f, "uint(v: {})", v),
+        QueryParam::Float(v) if v.is_finite() => {
+            let v = v.to_string();
+            if v.contains('.') {
+                f.write_str(&v)
+            } else {
+                write!(f, "{}.0", v)
+            }
+        }
+        QueryParam::Float(v) if v.is_nan() => f.write_str(r#"float(v: "NaN")"#),
+        QueryParam::Float(v) if *v > 0.0 => f.write_str(r#"float(v: "+Inf")"#),
+        QueryParam::Float(_) => f.write_str(r#"float(v: "-Inf")"#),
+        QueryParam::Bool(v) => write!(f, "{}", v),
+        QueryParam::Duration(v) => f.write_str(&format_duration(v)),
+        QueryParam::Time(v) => f.write_str(&v.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)),
+    }
+}
+
+fn write_string(f: &mut Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            '$' if chars.peek() == Some(&'{') => f.write_str("\\$")?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn render_pipeline() {
+        let query = from("telegraf")
+            .range(
+                chrono::Duration::hours(-1),
+                time(Utc.ymd(2023, 1, 2).and_hms(3, 4, 5)),
+            )
+            .filter(measurement().eq("cpu") & (tag("host").eq("a") | tag("host").eq("b")))
+            .aggregate_window(chrono::Duration::minutes(5), Aggregate::Mean)
+            .pivot_fields()
+            .yield_();
+
+        assert_eq!(
+            query.to_string(),
+            r#"from(bucket: "telegraf")
+    |> range(start: -1h, stop: 2023-01-02T03:04:05Z)
+    |> filter(fn: (r) => r._measurement == "cpu" and (r.host == "a" or r.host == "b"))
+    |> aggregateWindow(every: 5m, fn: mean)
+    |> pivot(rowKey: ["_time"], columnKey: ["_field"], valueColumn: "_value")
+    |> yield()"#
+        );
+    }
+
+    #[test]
+    fn escape_values() {
+        let query = from(r#"b") |> drop(columns: ["x"]) //"#)
+            .range_from(chrono::Duration::days(-7))
+            .filter(!tag("host name").eq("${secret}\\") & value().gt(1.0) & field().ne(10));
+
+        assert_eq!(
+            query.to_string(),
+            r#"from(bucket: "b\") |> drop(columns: [\"x\"]) //")
+    |> range(start: -7d)
+    |> filter(fn: (r) => not r["host name"] == "\${secret}\\" and r._value > 1.0 and r._field != 10)"#
+        );
+    }
+
+    #[test]
+    fn bind_params() {
+        let query: Query = from(param("bucket"))
+            .range(param_duration("start"), now())
+            .filter(tag("host").eq(param("host")))
+            .bind("bucket", "telegraf")
+            .bind("start", chrono::Duration::hours(-1))
+            .bind("host", "server01")
+            .into();
+
+        assert_eq!(
+            query.query,
+            r#"from(bucket: params.bucket)
+    |> range(start: duration(v: params.start), stop: now())
+    |> filter(fn: (r) => r.host == params.host)"#
+        );
+        assert_eq!(query.params["host"], QueryParam::from("server01"));
+        assert_eq!(
+            query.params["start"],
+            QueryParam::from(chrono::Duration::hours(-1))
+        );
+    }
+}
@@ -139,6 +139,14 @@ pub enum RequestError {
         /// Error description.
         text: String,
     },
+
+    /// While streaming a request or response body from or to a local
+    /// reader or writer, an I/O error occurred.
+    #[snafu(display("Error while performing I/O: {}", source))]
+    Io {
+        /// The underlying I/O error.
+        source: std::io::Error,
+    },
 }
 
 #[cfg(feature = "gzip")]
@@ -159,6 +167,8 @@ pub struct Client {
     reqwest: reqwest::Client,
     #[cfg(feature = "gzip")]
     compression: Compression,
+    spool: Option<std::sync::Arc<spool::Spool>>,
+    max_batch_size: Option<usize>,
 }
 
 impl Client {
@@ -200,6 +210,66 @@ impl Client {
     }
 }
 
+/// Options applied to a single request, overriding the client defaults.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RequestOptions {
+    /// Deadline for the whole request, including reading the response body
+    pub timeout: Option<std::time::Duration>,
+    /// Extra headers sent with the request
+    pub headers: Vec<(String, String)>,
+    /// Organization name used instead of the one tied to the client
+    pub org: Option<String>,
+    /// Organization ID used instead of the organization name
+    pub org_id: Option<String>,
+}
+
+impl RequestOptions {
+    /// Returns instance of RequestOptions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the request timeout
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Add an extra header
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Override the organization name
+    pub fn org(mut self, org: impl Into<String>) -> Self {
+        self.org = Some(org.into());
+        self
+    }
+
+    /// Override the organization by ID
+    pub fn org_id(mut self, org_id: impl Into<String>) -> Self {
+        self.org_id = Some(org_id.into());
+        self
+    }
+
+    /// Apply the options to a request sent on behalf of `org`, which is
+    /// passed as the `org` or `orgID` query parameter
+    fn apply(&self, org: &str, mut req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        req = match &self.org_id {
+            Some(org_id) => req.query(&[("orgID", org_id)]),
+            None => req.query(&[("org", self.org.as_deref().unwrap_or(org))]),
+        };
+        if let Some(timeout) = self.timeout {
+            req = req.timeout(timeout);
+        }
+        for (name, value) in &self.headers {
+            req = req.header(name.as_str(), value.as_str());
+        }
+        req
+    }
+}
+
 /// Errors that occur when building the client
 #[derive(Debug, Snafu)]
 pub enum BuildError {
@@ -209,6 +279,13 @@ pub enum BuildError {
         /// Reqwest internal error
         source: reqwest::Error,
     },
+
+    /// The spool directory could not be opened
+    #[snafu(display("Error while opening the spool: {}", source))]
+    SpoolError {
+        /// IO error
+        source: std::io::Error,
+    },
 }
 /// ClientBuilder builds the `Client`
 #[derive(Debug)]
@@ -221,6 +298,8 @@ pub struct ClientBuilder {
     reqwest: reqwest::ClientBuilder,
     #[cfg(feature = "gzip")]
     compression: Compression,
+    spool: Option<spool::SpoolOptions>,
+    max_batch_size: Option<usize>,
 }
 
 impl ClientBuilder {
@@ -260,6 +339,8 @@ impl ClientBuilder {
             reqwest: builder,
             #[cfg(feature = "gzip")]
             compression: Compression::None,
+            spool: None,
+            max_batch_size: None,
         }
     }
 
@@ -271,8 +352,29 @@ impl ClientBuilder {
         self
     }
 
+    /// Spool writes that fail because the server is unavailable to disk,
+    /// to be replayed once it is healthy again
+    pub fn spool(mut self, options: spool::SpoolOptions) -> Self {
+        self.spool = Some(options);
+        self
+    }
+
+    /// Split writes in several requests of at most `bytes` of line
+    /// protocol, before compression, so that they stay under the request
+    /// size limit of the server
+    pub fn max_batch_size(mut self, bytes: usize) -> Self {
+        self.max_batch_size = Some(bytes);
+        self
+    }
+
     /// Build returns the influx client
     pub fn build(self) -> Result<Client, BuildError> {
+        let spool = match self.spool {
+            Some(options) => Some(std::sync::Arc::new(
+                spool::Spool::open(options).context(SpoolError)?,
+            )),
+            None => None,
+        };
         Ok(Client {
             base: self.base,
             org: self.org,
@@ -280,6 +382,8 @@ impl ClientBuilder {
             reqwest: self.reqwest.build().context(ReqwestClientError)?,
             #[cfg(feature = "gzip")]
             compression: self.compression,
+            spool,
+            max_batch_size: self.max_batch_size,
         })
     }
 }
@@ -287,11 +391,14 @@ impl ClientBuilder {
 pub mod common;
 
 pub mod api;
+pub mod flux;
+pub mod model;
 pub mod models;
+pub mod spool;
 pub mod writable;
 
 // Re-exports
-pub use influxdb2_derive::FromDataPoint;
+pub use influxdb2_derive::{FromDataPoint, InfluxModel};
 pub use influxdb2_structmap::FromMap;
 
 #[cfg(test)]
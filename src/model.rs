@@ -0,0 +1,243 @@
+//! Model
+//!
+//! Structs read from and written to a measurement, see
+//! `#[derive(InfluxModel)]`.
+//!
+//! ```
+//! use influxdb2::flux;
+//! use influxdb2::model::InfluxModel;
+//! use influxdb2::models::Query;
+//!
+//! #[derive(Default, influxdb2::InfluxModel)]
+//! #[measurement = "cpu"]
+//! struct Cpu {
+//!     #[influxdb(tag)]
+//!     host: String,
+//!     usage: f64,
+//!     #[influxdb(timestamp)]
+//!     time: Option<chrono::DateTime<chrono::FixedOffset>>,
+//! }
+//!
+//! let query: Query = Cpu::query(
+//!     "bucket",
+//!     chrono::Duration::hours(-1),
+//!     flux::now(),
+//!     Some(Cpu::tag("host").eq("server01")),
+//! )
+//! .into();
+//! ```
+
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, FixedOffset, Utc};
+
+use crate::flux::{self, Column, Expr, Pipeline, Predicate};
+use crate::models::WriteDataPoint;
+use crate::FromMap;
+
+pub use influxdb2_structmap::value::Value;
+pub use influxdb2_structmap::GenericMap;
+
+/// A struct mapped to a measurement, implemented by `#[derive(InfluxModel)]`
+pub trait InfluxModel: FromMap + WriteDataPoint {
+    /// Measurement of the model, `None` when taken from a member
+    const MEASUREMENT: Option<&'static str>;
+    /// Keys of the tags
+    const TAGS: &'static [&'static str];
+    /// Keys of the fields
+    const FIELDS: &'static [&'static str];
+
+    /// Column of a tag of the model
+    ///
+    /// # Panics
+    ///
+    /// When `name` is not one of `TAGS`.
+    fn tag(name: &str) -> Column {
+        assert!(
+            Self::TAGS.contains(&name),
+            "`{}` is not a tag of the model",
+            name
+        );
+        flux::tag(name)
+    }
+
+    /// Reads the model from a bucket: the fields of its measurement over the
+    /// range, filtered by `filter`, pivoted into one row per point.
+    fn query(
+        bucket: impl Into<Expr>,
+        start: impl Into<Expr>,
+        stop: impl Into<Expr>,
+        filter: Option<Predicate>,
+    ) -> Pipeline {
+        let mut predicate = Self::FIELDS
+            .iter()
+            .map(|key| flux::field().eq(*key))
+            .reduce(|fields, field| fields | field)
+            .expect("a model has at least one field");
+        if let Some(measurement) = Self::MEASUREMENT {
+            predicate = flux::measurement().eq(measurement) & predicate;
+        }
+
+        let mut pipeline = flux::from(bucket).range(start, stop);
+        if let Some(filter) = filter {
+            pipeline = pipeline.filter(filter);
+        }
+        pipeline.filter(predicate).pivot_fields()
+    }
+}
+
+/// Conversion of the value of a column, the counterpart of the `writable`
+/// traits
+pub trait ValueReadable: Sized {
+    /// Converts a value, `None` when it is not of the expected type
+    fn read_value(value: Value) -> Option<Self>;
+
+    /// Converts the value of a column that may be missing from the row
+    fn read_column(value: Option<Value>) -> Option<Self> {
+        value.and_then(Self::read_value)
+    }
+}
+
+impl ValueReadable for f64 {
+    fn read_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Double(v) => Some(v.into_inner()),
+            _ => None,
+        }
+    }
+}
+
+impl ValueReadable for i64 {
+    fn read_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Long(v) => Some(v),
+            Value::TimeRFC(v) => Some(v.timestamp_nanos()),
+            _ => None,
+        }
+    }
+}
+
+impl ValueReadable for u64 {
+    fn read_value(value: Value) -> Option<Self> {
+        match value {
+            Value::UnsignedLong(v) => Some(v),
+            Value::TimeRFC(v) => Self::try_from(v.timestamp_nanos()).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl ValueReadable for bool {
+    fn read_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl ValueReadable for String {
+    fn read_value(value: Value) -> Option<Self> {
+        match value {
+            Value::String(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl ValueReadable for Vec<u8> {
+    fn read_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Base64Binary(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl ValueReadable for chrono::Duration {
+    fn read_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Duration(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl ValueReadable for DateTime<FixedOffset> {
+    fn read_value(value: Value) -> Option<Self> {
+        match value {
+            Value::TimeRFC(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl ValueReadable for DateTime<Utc> {
+    fn read_value(value: Value) -> Option<Self> {
+        DateTime::<FixedOffset>::read_value(value).map(Into::into)
+    }
+}
+
+impl ValueReadable for SystemTime {
+    fn read_value(value: Value) -> Option<Self> {
+        let nanos = i64::read_value(value)?;
+        let offset = Duration::from_nanos(nanos.unsigned_abs());
+        if nanos < 0 {
+            UNIX_EPOCH.checked_sub(offset)
+        } else {
+            UNIX_EPOCH.checked_add(offset)
+        }
+    }
+}
+
+impl<T: ValueReadable> ValueReadable for Option<T> {
+    fn read_value(value: Value) -> Option<Self> {
+        Some(T::read_value(value))
+    }
+
+    fn read_column(value: Option<Value>) -> Option<Self> {
+        Some(value.and_then(T::read_value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn read_values() {
+        let time = FixedOffset::east(3600).ymd(2001, 9, 9).and_hms(2, 46, 40);
+
+        assert_eq!(f64::read_value(Value::Double(1.5.into())), Some(1.5));
+        assert_eq!(f64::read_value(Value::Long(1)), None);
+        assert_eq!(
+            i64::read_value(Value::TimeRFC(time)),
+            Some(1_000_000_000_000_000_000)
+        );
+        assert_eq!(
+            u64::read_value(Value::TimeRFC(time)),
+            Some(1_000_000_000_000_000_000)
+        );
+        assert_eq!(
+            u64::read_value(Value::TimeRFC(Utc.ymd(1969, 1, 1).and_hms(0, 0, 0).into())),
+            None
+        );
+        assert_eq!(
+            SystemTime::read_value(Value::TimeRFC(time)),
+            Some(UNIX_EPOCH + Duration::from_secs(1_000_000_000))
+        );
+        assert_eq!(
+            DateTime::<Utc>::read_value(Value::TimeRFC(time)),
+            Some(Utc.ymd(2001, 9, 9).and_hms(1, 46, 40))
+        );
+
+        assert_eq!(String::read_column(None), None);
+        assert_eq!(Option::<String>::read_column(None), Some(None));
+        assert_eq!(
+            Option::<String>::read_column(Some(Value::String("a".to_owned()))),
+            Some(Some("a".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Annotations
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// AnnotationCreate : Annotation to create or the new state of an annotation
+/// to update.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationCreate {
+    /// Name of the stream the annotation belongs to. Defaults to "default"
+    /// on the server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<String>,
+    /// Short summary of the event
+    pub summary: String,
+    /// Longer description of the event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Key/Value pairs used to filter annotations
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub stickers: HashMap<String, String>,
+    /// Start of the event. Defaults to the server's now time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<DateTime<Utc>>,
+    /// End of the event. Defaults to `start_time`, which marks a point in
+    /// time rather than a range.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl AnnotationCreate {
+    /// Returns an instance of AnnotationCreate
+    pub fn new(summary: String) -> Self {
+        Self {
+            summary,
+            ..Default::default()
+        }
+    }
+}
+
+/// AnnotationEvent : An annotation as stored on the server
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationEvent {
+    /// Annotation ID
+    pub id: String,
+    /// Name of the stream the annotation belongs to
+    pub stream: String,
+    /// Short summary of the event
+    pub summary: String,
+    /// Longer description of the event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Key/Value pairs used to filter annotations
+    #[serde(default)]
+    pub stickers: HashMap<String, String>,
+    /// Start of the event
+    pub start_time: DateTime<Utc>,
+    /// End of the event
+    pub end_time: DateTime<Utc>,
+}
+
+/// StreamCreate : Annotation stream to create or update
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamCreate {
+    /// Stream name
+    pub stream: String,
+    /// Stream description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl StreamCreate {
+    /// Returns an instance of StreamCreate
+    pub fn new(stream: String) -> Self {
+        Self {
+            stream,
+            description: None,
+        }
+    }
+}
+
+/// AnnotationStream : An annotation stream as stored on the server
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationStream {
+    /// Stream ID
+    pub id: String,
+    /// Stream name
+    pub stream: String,
+    /// Stream description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Stream created timestamp
+    pub created_at: DateTime<Utc>,
+    /// Stream updated timestamp
+    pub updated_at: DateTime<Utc>,
+}
@@ -2,23 +2,43 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::models::ast::{Expression, Property};
+
 /// Represents a function call
-#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct CallExpression {
-    /// Type of AST node
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub r#type: Option<String>,
     /// Callee
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub callee: Option<Box<crate::models::ast::Expression>>,
+    pub callee: Box<Expression>,
     /// Function arguments
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub arguments: Vec<crate::models::ast::Expression>,
+    pub arguments: Vec<Expression>,
 }
 
 impl CallExpression {
-    /// Represents a function call
-    pub fn new() -> Self {
-        Self::default()
+    /// Call with named arguments, passed as a single object like Flux does
+    pub fn with_arguments(callee: Expression, arguments: Vec<Property>) -> Self {
+        Self {
+            callee: Box::new(callee),
+            arguments: if arguments.is_empty() {
+                vec![]
+            } else {
+                vec![Expression::object(arguments)]
+            },
+        }
+    }
+
+    /// Named arguments of the call
+    pub fn properties(&self) -> impl Iterator<Item = &Property> {
+        self.arguments.iter().flat_map(|argument| match argument {
+            Expression::Object(object) => object.properties.iter(),
+            _ => [].iter(),
+        })
+    }
+
+    /// Value of the named argument `name`
+    pub fn argument(&self, name: &str) -> Option<&Expression> {
+        self.properties()
+            .find(|property| property.key.name() == name)
+            .and_then(|property| property.value.as_ref())
     }
 }
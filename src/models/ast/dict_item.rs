@@ -2,23 +2,13 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::models::ast::Expression;
+
 /// A key/value pair in a dictionary
-#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DictItem {
-    /// Type of AST node
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub r#type: Option<String>,
     /// Key
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub key: Option<crate::models::ast::Expression>,
+    pub key: Expression,
     /// Value
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub val: Option<crate::models::ast::Expression>,
-}
-
-impl DictItem {
-    /// A key/value pair in a dictionary
-    pub fn new() -> Self {
-        Self::default()
-    }
+    pub val: Expression,
 }
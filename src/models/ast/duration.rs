@@ -7,21 +7,19 @@ use serde::{Deserialize, Serialize};
 /// composed.
 #[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
 pub struct Duration {
-    /// Type of AST node
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub r#type: Option<String>,
     /// Duration Magnitude
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub magnitude: Option<i32>,
+    pub magnitude: i64,
     /// Duration unit
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub unit: Option<String>,
+    pub unit: String,
 }
 
 impl Duration {
     /// A pair consisting of length of time and the unit of time measured. It is
     /// the atomic unit from which all duration literals are composed.
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(magnitude: i64, unit: &str) -> Self {
+        Self {
+            magnitude,
+            unit: unit.to_owned(),
+        }
     }
 }
@@ -1,84 +1,587 @@
 //! Expression
 
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, SecondsFormat, TimeZone};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::models::ast::{
+    BooleanLiteral, CallExpression, DateTimeLiteral, DictItem, Duration, DurationLiteral,
+    FloatLiteral, Identifier, IntegerLiteral, LabelLiteral, Literal, MemberExpression, Property,
+    RegexpLiteral, Statement, StringLiteral, UnsignedIntegerLiteral,
+};
 
 /// Expression AST
+///
+/// Serialized with a `type` field naming the kind of node, like the
+/// `/api/v2/query/ast` endpoint returns it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression {
+    /// Identifier, e.g. `r`
+    Identifier(Identifier),
+    /// Literal value, e.g. `"cpu"` or `1h`
+    Literal(Literal),
+    /// Array, e.g. `[1, 2]`
+    Array(ArrayExpression),
+    /// Dictionary, e.g. `["a": 1]`
+    Dict(DictExpression),
+    /// Function, e.g. `(r) => r._value > 0`
+    Function(FunctionExpression),
+    /// Binary operation, e.g. `r.host == "a"`
+    Binary(BinaryExpression),
+    /// Logical operation, `and` or `or`
+    Logical(LogicalExpression),
+    /// Unary operation, e.g. `not ok`
+    Unary(UnaryExpression),
+    /// Function call, e.g. `range(start: -1h)`
+    Call(CallExpression),
+    /// Pipe forward, e.g. `data |> range(start: -1h)`
+    Pipe(PipeExpression),
+    /// Member access, e.g. `r._measurement`
+    Member(MemberExpression),
+    /// Index access, e.g. `a[0]`
+    Index(IndexExpression),
+    /// Object, e.g. `{a: 1}`
+    Object(ObjectExpression),
+    /// Conditional, e.g. `if ok then 1 else 0`
+    Conditional(ConditionalExpression),
+    /// Parenthesized expression
+    Paren(ParenExpression),
+    /// Interpolated string, e.g. `"host ${name}"`
+    String(StringExpression),
+    /// Source that could not be parsed
+    Bad(BadExpression),
+}
+
+/// Used to create and directly specify the elements of an array object
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct ArrayExpression {
+    /// Elements of the array
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub elements: Vec<Expression>,
+}
+
+/// Used to create and directly specify the elements of a dictionary
 #[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
-pub struct Expression {
-    /// Type of AST node
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub r#type: Option<String>,
+pub struct DictExpression {
     /// Elements of the dictionary
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub elements: Vec<crate::models::ast::DictItem>,
+    pub elements: Vec<DictItem>,
+}
+
+/// Function expression
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FunctionExpression {
     /// Function parameters
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub params: Vec<crate::models::ast::Property>,
-    /// Node
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub body: Option<crate::models::ast::Node>,
+    pub params: Vec<Property>,
+    /// Function body
+    pub body: FunctionBody,
+}
+
+/// Body of a function, a block of statements or a single expression
+#[derive(Clone, Debug, PartialEq)]
+pub enum FunctionBody {
+    /// Block of statements, e.g. `{ x = 1 return x }`
+    Block(Block),
+    /// Expression, e.g. `r._value > 0`
+    Expression(Box<Expression>),
+}
+
+/// Set of statements
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct Block {
+    /// Block body
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub body: Vec<Statement>,
+}
+
+/// Uses binary operators to act on two operands in an expression
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BinaryExpression {
     /// Operator
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub operator: Option<String>,
+    pub operator: String,
     /// Left leaf
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub left: Option<Box<crate::models::ast::Expression>>,
+    pub left: Box<Expression>,
     /// Right leaf
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub right: Option<Box<crate::models::ast::Expression>>,
-    /// Parent Expression
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub callee: Option<Box<crate::models::ast::Expression>>,
-    /// Function arguments
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub arguments: Vec<crate::models::ast::Expression>,
-    /// Test Expr
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub test: Option<Box<crate::models::ast::Expression>>,
-    /// Alternate Expr
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub alternate: Option<Box<crate::models::ast::Expression>>,
-    /// Consequent Expr
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub consequent: Option<Box<crate::models::ast::Expression>>,
-    /// Object Expr
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub object: Option<Box<crate::models::ast::Expression>>,
-    /// PropertyKey Expr
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub property: Option<Box<crate::models::ast::PropertyKey>>,
+    pub right: Box<Expression>,
+}
+
+/// Represents the rule conditions that collectively evaluate to either true
+/// or false
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LogicalExpression {
+    /// Operator
+    pub operator: String,
+    /// Left leaf
+    pub left: Box<Expression>,
+    /// Right leaf
+    pub right: Box<Expression>,
+}
+
+/// Uses operators to act on a single operand in an expression
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UnaryExpression {
+    /// Operator
+    pub operator: String,
+    /// Argument
+    pub argument: Box<Expression>,
+}
+
+/// Call expression with pipe argument
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PipeExpression {
+    /// Piped argument
+    pub argument: Box<Expression>,
+    /// Call receiving the argument
+    pub call: CallExpression,
+}
+
+/// A placeholder for expressions for which no correct expression nodes can
+/// be created
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct BadExpression {
+    /// Raw source text
+    #[serde(default)]
+    pub text: String,
+    /// Part of the expression that could be parsed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expression: Option<Box<Expression>>,
+}
+
+/// Represents indexing into an array
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexExpression {
     /// Array Expr
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub array: Option<Box<crate::models::ast::Expression>>,
+    pub array: Box<Expression>,
     /// Index Expr
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub index: Option<Box<crate::models::ast::Expression>>,
-    /// Properties
+    pub index: Box<Expression>,
+}
+
+/// Allows the declaration of an anonymous object within a declaration
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct ObjectExpression {
+    /// Object being extended, e.g. `{r with a: 1}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub with: Option<Identifier>,
+    /// Object properties
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub properties: Vec<crate::models::ast::Property>,
+    pub properties: Vec<Property>,
+}
+
+/// Selects one of two expressions, `Alternate` or `Consequent`, depending on
+/// a third boolean expression, `Test`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConditionalExpression {
+    /// Test Expr
+    pub test: Box<Expression>,
+    /// Consequent Expr
+    pub consequent: Box<Expression>,
+    /// Alternate Expr
+    pub alternate: Box<Expression>,
+}
+
+/// Represents an expression wrapped in parenthesis
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParenExpression {
     /// Expression
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub expression: Option<Box<crate::models::ast::Expression>>,
-    /// Argument
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub argument: Option<Box<crate::models::ast::Expression>>,
-    /// Call Expr
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub call: Option<crate::models::ast::CallExpression>,
-    /// Expression Value
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub value: Option<String>,
-    /// Duration values
+    pub expression: Box<Expression>,
+}
+
+/// String with interpolated expressions
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct StringExpression {
+    /// Parts of the string
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub values: Vec<crate::models::ast::Duration>,
-    /// Expression Name
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
+    pub parts: Vec<StringExpressionPart>,
+}
+
+/// Part of an interpolated string
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum StringExpressionPart {
+    /// Literal text
+    #[serde(rename = "TextPart")]
+    Text {
+        /// Text
+        value: String,
+    },
+    /// Interpolated expression, `${...}`
+    #[serde(rename = "InterpolatedPart")]
+    Interpolated {
+        /// Expression
+        expression: Expression,
+    },
 }
 
 impl Expression {
-    /// Return instance of expression
-    pub fn new() -> Self {
-        Self::default()
+    /// Identifier, e.g. `r`
+    pub fn identifier(name: &str) -> Self {
+        Self::Identifier(Identifier::new(name))
+    }
+
+    /// String literal, e.g. `"cpu"`
+    pub fn string(value: &str) -> Self {
+        Self::Literal(Literal::String(StringLiteral::new(value)))
+    }
+
+    /// Integer literal, e.g. `10`
+    pub fn integer(value: i64) -> Self {
+        Self::Literal(Literal::Integer(IntegerLiteral {
+            value: value.to_string(),
+        }))
+    }
+
+    /// Float literal, e.g. `1.5`
+    pub fn float(value: f64) -> Self {
+        Self::Literal(Literal::Float(FloatLiteral { value }))
+    }
+
+    /// Boolean literal, `true` or `false`
+    pub fn boolean(value: bool) -> Self {
+        Self::Literal(Literal::Boolean(BooleanLiteral { value }))
+    }
+
+    /// Date time literal, e.g. `2023-01-01T00:00:00Z`
+    pub fn date_time<Tz: TimeZone>(value: DateTime<Tz>) -> Self
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        Self::Literal(Literal::DateTime(DateTimeLiteral {
+            value: value.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        }))
+    }
+
+    /// Duration literal, e.g. `-1h`
+    pub fn duration(magnitude: i64, unit: &str) -> Self {
+        Self::Literal(Literal::Duration(DurationLiteral {
+            values: vec![Duration::new(magnitude, unit)],
+        }))
+    }
+
+    /// Regular expression literal, e.g. `/^cpu/`
+    pub fn regexp(value: &str) -> Self {
+        Self::Literal(Literal::Regexp(RegexpLiteral {
+            value: value.to_owned(),
+        }))
+    }
+
+    /// Member access, e.g. `r._measurement`
+    pub fn member(object: Self, property: &str) -> Self {
+        Self::Member(MemberExpression::new(object, property))
+    }
+
+    /// Function call with named arguments, e.g. `range(start: -1h)`
+    pub fn call(callee: Self, arguments: Vec<Property>) -> Self {
+        Self::Call(CallExpression::with_arguments(callee, arguments))
+    }
+
+    /// Pipe `argument` into a call, e.g. `data |> range(start: -1h)`
+    pub fn pipe(argument: Self, callee: Self, arguments: Vec<Property>) -> Self {
+        Self::Pipe(PipeExpression {
+            argument: Box::new(argument),
+            call: CallExpression::with_arguments(callee, arguments),
+        })
+    }
+
+    /// Array, e.g. `[1, 2]`
+    pub fn array(elements: Vec<Self>) -> Self {
+        Self::Array(ArrayExpression { elements })
+    }
+
+    /// Object, e.g. `{a: 1}`
+    pub fn object(properties: Vec<Property>) -> Self {
+        Self::Object(ObjectExpression {
+            with: None,
+            properties,
+        })
+    }
+
+    /// Binary expression, e.g. `r.host == "a"`
+    pub fn binary(operator: &str, left: Self, right: Self) -> Self {
+        Self::Binary(BinaryExpression {
+            operator: operator.to_owned(),
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+    }
+
+    /// Logical expression, `and` or `or`
+    pub fn logical(operator: &str, left: Self, right: Self) -> Self {
+        Self::Logical(LogicalExpression {
+            operator: operator.to_owned(),
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+    }
+
+    /// Unary expression, e.g. `not ok`
+    pub fn unary(operator: &str, argument: Self) -> Self {
+        Self::Unary(UnaryExpression {
+            operator: operator.to_owned(),
+            argument: Box::new(argument),
+        })
+    }
+
+    /// Function, e.g. `(r) => r._value > 0`
+    pub fn function(params: &[&str], body: Self) -> Self {
+        Self::Function(FunctionExpression {
+            params: params.iter().map(|name| Property::param(name)).collect(),
+            body: FunctionBody::Expression(Box::new(body)),
+        })
+    }
+}
+
+/// Every node that can appear where an expression is expected, tagged the
+/// way Flux serializes it. Literals are flattened into it so `Expression`
+/// can group them under a single variant.
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum Tagged {
+    Identifier(Identifier),
+    BooleanLiteral(BooleanLiteral),
+    DateTimeLiteral(DateTimeLiteral),
+    DurationLiteral(DurationLiteral),
+    FloatLiteral(FloatLiteral),
+    IntegerLiteral(IntegerLiteral),
+    PipeLiteral,
+    RegexpLiteral(RegexpLiteral),
+    StringLiteral(StringLiteral),
+    UnsignedIntegerLiteral(UnsignedIntegerLiteral),
+    LabelLiteral(LabelLiteral),
+    ArrayExpression(ArrayExpression),
+    DictExpression(DictExpression),
+    FunctionExpression(FunctionExpression),
+    BinaryExpression(BinaryExpression),
+    LogicalExpression(LogicalExpression),
+    UnaryExpression(UnaryExpression),
+    CallExpression(CallExpression),
+    PipeExpression(PipeExpression),
+    MemberExpression(MemberExpression),
+    IndexExpression(IndexExpression),
+    ObjectExpression(ObjectExpression),
+    ConditionalExpression(ConditionalExpression),
+    ParenExpression(ParenExpression),
+    StringExpression(StringExpression),
+    BadExpression(BadExpression),
+    Block(Block),
+}
+
+impl Tagged {
+    fn into_expression(self) -> Result<Expression, Block> {
+        Ok(match self {
+            Self::Identifier(v) => Expression::Identifier(v),
+            Self::BooleanLiteral(v) => Expression::Literal(Literal::Boolean(v)),
+            Self::DateTimeLiteral(v) => Expression::Literal(Literal::DateTime(v)),
+            Self::DurationLiteral(v) => Expression::Literal(Literal::Duration(v)),
+            Self::FloatLiteral(v) => Expression::Literal(Literal::Float(v)),
+            Self::IntegerLiteral(v) => Expression::Literal(Literal::Integer(v)),
+            Self::PipeLiteral => Expression::Literal(Literal::Pipe),
+            Self::RegexpLiteral(v) => Expression::Literal(Literal::Regexp(v)),
+            Self::StringLiteral(v) => Expression::Literal(Literal::String(v)),
+            Self::UnsignedIntegerLiteral(v) => Expression::Literal(Literal::UnsignedInteger(v)),
+            Self::LabelLiteral(v) => Expression::Literal(Literal::Label(v)),
+            Self::ArrayExpression(v) => Expression::Array(v),
+            Self::DictExpression(v) => Expression::Dict(v),
+            Self::FunctionExpression(v) => Expression::Function(v),
+            Self::BinaryExpression(v) => Expression::Binary(v),
+            Self::LogicalExpression(v) => Expression::Logical(v),
+            Self::UnaryExpression(v) => Expression::Unary(v),
+            Self::CallExpression(v) => Expression::Call(v),
+            Self::PipeExpression(v) => Expression::Pipe(v),
+            Self::MemberExpression(v) => Expression::Member(v),
+            Self::IndexExpression(v) => Expression::Index(v),
+            Self::ObjectExpression(v) => Expression::Object(v),
+            Self::ConditionalExpression(v) => Expression::Conditional(v),
+            Self::ParenExpression(v) => Expression::Paren(v),
+            Self::StringExpression(v) => Expression::String(v),
+            Self::BadExpression(v) => Expression::Bad(v),
+            Self::Block(block) => return Err(block),
+        })
+    }
+}
+
+/// Borrowed counterpart of `Tagged` used for serialization
+#[derive(Serialize)]
+#[serde(tag = "type")]
+enum TaggedRef<'a> {
+    Identifier(&'a Identifier),
+    BooleanLiteral(&'a BooleanLiteral),
+    DateTimeLiteral(&'a DateTimeLiteral),
+    DurationLiteral(&'a DurationLiteral),
+    FloatLiteral(&'a FloatLiteral),
+    IntegerLiteral(&'a IntegerLiteral),
+    PipeLiteral,
+    RegexpLiteral(&'a RegexpLiteral),
+    StringLiteral(&'a StringLiteral),
+    UnsignedIntegerLiteral(&'a UnsignedIntegerLiteral),
+    LabelLiteral(&'a LabelLiteral),
+    ArrayExpression(&'a ArrayExpression),
+    DictExpression(&'a DictExpression),
+    FunctionExpression(&'a FunctionExpression),
+    BinaryExpression(&'a BinaryExpression),
+    LogicalExpression(&'a LogicalExpression),
+    UnaryExpression(&'a UnaryExpression),
+    CallExpression(&'a CallExpression),
+    PipeExpression(&'a PipeExpression),
+    MemberExpression(&'a MemberExpression),
+    IndexExpression(&'a IndexExpression),
+    ObjectExpression(&'a ObjectExpression),
+    ConditionalExpression(&'a ConditionalExpression),
+    ParenExpression(&'a ParenExpression),
+    StringExpression(&'a StringExpression),
+    BadExpression(&'a BadExpression),
+    Block(&'a Block),
+}
+
+impl<'a> From<&'a Expression> for TaggedRef<'a> {
+    fn from(expression: &'a Expression) -> Self {
+        match expression {
+            Expression::Identifier(v) => Self::Identifier(v),
+            Expression::Literal(Literal::Boolean(v)) => Self::BooleanLiteral(v),
+            Expression::Literal(Literal::DateTime(v)) => Self::DateTimeLiteral(v),
+            Expression::Literal(Literal::Duration(v)) => Self::DurationLiteral(v),
+            Expression::Literal(Literal::Float(v)) => Self::FloatLiteral(v),
+            Expression::Literal(Literal::Integer(v)) => Self::IntegerLiteral(v),
+            Expression::Literal(Literal::Pipe) => Self::PipeLiteral,
+            Expression::Literal(Literal::Regexp(v)) => Self::RegexpLiteral(v),
+            Expression::Literal(Literal::String(v)) => Self::StringLiteral(v),
+            Expression::Literal(Literal::UnsignedInteger(v)) => Self::UnsignedIntegerLiteral(v),
+            Expression::Literal(Literal::Label(v)) => Self::LabelLiteral(v),
+            Expression::Array(v) => Self::ArrayExpression(v),
+            Expression::Dict(v) => Self::DictExpression(v),
+            Expression::Function(v) => Self::FunctionExpression(v),
+            Expression::Binary(v) => Self::BinaryExpression(v),
+            Expression::Logical(v) => Self::LogicalExpression(v),
+            Expression::Unary(v) => Self::UnaryExpression(v),
+            Expression::Call(v) => Self::CallExpression(v),
+            Expression::Pipe(v) => Self::PipeExpression(v),
+            Expression::Member(v) => Self::MemberExpression(v),
+            Expression::Index(v) => Self::IndexExpression(v),
+            Expression::Object(v) => Self::ObjectExpression(v),
+            Expression::Conditional(v) => Self::ConditionalExpression(v),
+            Expression::Paren(v) => Self::ParenExpression(v),
+            Expression::String(v) => Self::StringExpression(v),
+            Expression::Bad(v) => Self::BadExpression(v),
+        }
+    }
+}
+
+impl Serialize for Expression {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TaggedRef::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Expression {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Tagged::deserialize(deserializer)?
+            .into_expression()
+            .map_err(|_| D::Error::custom("expected an expression, found a Block"))
+    }
+}
+
+impl Serialize for FunctionBody {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Block(block) => TaggedRef::Block(block).serialize(serializer),
+            Self::Expression(expression) => expression.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FunctionBody {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Tagged::deserialize(deserializer)?.into_expression() {
+            Ok(expression) => Self::Expression(Box::new(expression)),
+            Err(block) => Self::Block(block),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ast::PropertyKey;
+
+    #[test]
+    fn deserialize_expression() {
+        let json = r#"{
+            "type": "FunctionExpression",
+            "params": [{"type": "Property", "key": {"type": "Identifier", "name": "r"}}],
+            "body": {
+                "type": "Block",
+                "body": [{
+                    "type": "ReturnStatement",
+                    "argument": {
+                        "type": "BinaryExpression",
+                        "operator": ">",
+                        "left": {
+                            "type": "MemberExpression",
+                            "object": {"type": "Identifier", "name": "r"},
+                            "property": {"type": "StringLiteral", "value": "_value"}
+                        },
+                        "right": {"type": "FloatLiteral", "value": 1.5}
+                    }
+                }]
+            }
+        }"#;
+
+        let expression: Expression = serde_json::from_str(json).unwrap();
+
+        let function = match &expression {
+            Expression::Function(function) => function,
+            other => panic!("expected a function, got {:?}", other),
+        };
+        assert_eq!(function.params, vec![Property::param("r")]);
+        match &function.body {
+            FunctionBody::Block(block) => match &block.body[..] {
+                [Statement::Return(ret)] => assert_eq!(
+                    ret.argument,
+                    Expression::binary(
+                        ">",
+                        Expression::Member(MemberExpression {
+                            object: Box::new(Expression::identifier("r")),
+                            property: PropertyKey::string("_value"),
+                        }),
+                        Expression::float(1.5),
+                    )
+                ),
+                other => panic!("expected a return statement, got {:?}", other),
+            },
+            other => panic!("expected a block, got {:?}", other),
+        }
+
+        let round_trip: Expression =
+            serde_json::from_value(serde_json::to_value(&expression).unwrap()).unwrap();
+        assert_eq!(round_trip, expression);
+    }
+
+    #[test]
+    fn serialize_expression() {
+        let expression = Expression::pipe(
+            Expression::identifier("data"),
+            Expression::identifier("range"),
+            vec![Property::with_value("start", Expression::duration(-1, "h"))],
+        );
+
+        assert_eq!(
+            serde_json::to_value(&expression).unwrap(),
+            serde_json::json!({
+                "type": "PipeExpression",
+                "argument": {"type": "Identifier", "name": "data"},
+                "call": {
+                    "callee": {"type": "Identifier", "name": "range"},
+                    "arguments": [{
+                        "type": "ObjectExpression",
+                        "properties": [{
+                            "key": {"type": "Identifier", "name": "start"},
+                            "value": {
+                                "type": "DurationLiteral",
+                                "values": [{"magnitude": -1, "unit": "h"}]
+                            }
+                        }]
+                    }]
+                }
+            })
+        );
     }
 }
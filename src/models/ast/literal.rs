@@ -0,0 +1,102 @@
+//! Literal
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::ast::{Duration, StringLiteral};
+
+/// Literal value
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Literal {
+    /// Boolean, `true` or `false`
+    #[serde(rename = "BooleanLiteral")]
+    Boolean(BooleanLiteral),
+    /// Point in time, e.g. `2023-01-01T00:00:00Z`
+    #[serde(rename = "DateTimeLiteral")]
+    DateTime(DateTimeLiteral),
+    /// Duration, e.g. `1h30m`
+    #[serde(rename = "DurationLiteral")]
+    Duration(DurationLiteral),
+    /// Float, e.g. `1.5`
+    #[serde(rename = "FloatLiteral")]
+    Float(FloatLiteral),
+    /// Integer, e.g. `10`
+    #[serde(rename = "IntegerLiteral")]
+    Integer(IntegerLiteral),
+    /// Pipe receive, `<-`
+    #[serde(rename = "PipeLiteral")]
+    Pipe,
+    /// Regular expression, e.g. `/^cpu/`
+    #[serde(rename = "RegexpLiteral")]
+    Regexp(RegexpLiteral),
+    /// String, e.g. `"cpu"`
+    #[serde(rename = "StringLiteral")]
+    String(StringLiteral),
+    /// Unsigned integer
+    #[serde(rename = "UnsignedIntegerLiteral")]
+    UnsignedInteger(UnsignedIntegerLiteral),
+    /// Label, e.g. `.host`
+    #[serde(rename = "LabelLiteral")]
+    Label(LabelLiteral),
+}
+
+/// Represents boolean values
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct BooleanLiteral {
+    /// BooleanLiteral Value
+    pub value: bool,
+}
+
+/// Represents an instant in time with nanosecond precision using the syntax
+/// of golang's RFC3339 Nanosecond variant
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct DateTimeLiteral {
+    /// DateTimeLiteral Value
+    pub value: String,
+}
+
+/// Represents the elapsed time between two instants as an int64 nanosecond
+/// count with syntax of golang's time.Duration
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct DurationLiteral {
+    /// Duration values
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<Duration>,
+}
+
+/// Represents floating point numbers according to the double representations
+/// defined by the IEEE-754-1985
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct FloatLiteral {
+    /// FloatLiteral Value
+    pub value: f64,
+}
+
+/// Represents integer numbers
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct IntegerLiteral {
+    /// IntegerLiteral Value, as written in the source
+    pub value: String,
+}
+
+/// Expressions begin and end with `/` and are regular expressions with syntax
+/// accepted by RE2
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct RegexpLiteral {
+    /// RegexpLiteral Value
+    pub value: String,
+}
+
+/// Represents integer numbers
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct UnsignedIntegerLiteral {
+    /// UnsignedIntegerLiteral Value, as written in the source
+    pub value: String,
+}
+
+/// Names a record property, e.g. `.host`
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct LabelLiteral {
+    /// LabelLiteral Value, without the leading `.`
+    pub value: String,
+}
@@ -2,23 +2,23 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::models::ast::{Expression, PropertyKey};
+
 /// Represents accessing a property of an object
-#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MemberExpression {
-    /// Type of AST node
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub r#type: Option<String>,
     /// Member object
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub object: Option<crate::models::ast::Expression>,
+    pub object: Box<Expression>,
     /// Member Property
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub property: Option<crate::models::ast::PropertyKey>,
+    pub property: PropertyKey,
 }
 
 impl MemberExpression {
-    /// Represents accessing a property of an object
-    pub fn new() -> Self {
-        Self::default()
+    /// Access of `property` on `object`, e.g. `r._measurement`
+    pub fn new(object: Expression, property: &str) -> Self {
+        Self {
+            object: Box::new(object),
+            property: PropertyKey::identifier(property),
+        }
     }
 }
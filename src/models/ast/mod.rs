@@ -3,9 +3,22 @@
 pub mod identifier;
 pub use self::identifier::Identifier;
 pub mod statement;
-pub use self::statement::Statement;
+pub use self::statement::{
+    Assignment, BadStatement, BuiltinStatement, ExpressionStatement, OptionStatement,
+    ReturnStatement, Statement, TestCaseStatement, TestStatement,
+};
 pub mod expression;
-pub use self::expression::Expression;
+pub use self::expression::{
+    ArrayExpression, BadExpression, BinaryExpression, Block, ConditionalExpression, DictExpression,
+    Expression, FunctionBody, FunctionExpression, IndexExpression, LogicalExpression,
+    ObjectExpression, ParenExpression, PipeExpression, StringExpression, StringExpressionPart,
+    UnaryExpression,
+};
+pub mod literal;
+pub use self::literal::{
+    BooleanLiteral, DateTimeLiteral, DurationLiteral, FloatLiteral, IntegerLiteral, LabelLiteral,
+    Literal, RegexpLiteral, UnsignedIntegerLiteral,
+};
 pub mod call_expression;
 pub use self::call_expression::CallExpression;
 pub mod member_expression;
@@ -15,9 +28,7 @@ pub use self::string_literal::StringLiteral;
 pub mod dict_item;
 pub use self::dict_item::DictItem;
 pub mod variable_assignment;
-pub use self::variable_assignment::VariableAssignment;
-pub mod node;
-pub use self::node::Node;
+pub use self::variable_assignment::{MemberAssignment, VariableAssignment};
 pub mod property;
 pub use self::property::Property;
 pub mod property_key;
@@ -32,3 +43,6 @@ pub mod package_clause;
 pub use self::package_clause::PackageClause;
 pub mod duration;
 pub use self::duration::Duration;
+pub mod visitor;
+pub use self::visitor::Visitor;
+mod printer;
@@ -2,20 +2,20 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::models::ast::Identifier;
+
 /// Defines a package identifier
 #[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
 pub struct PackageClause {
-    /// Type of AST node
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub r#type: Option<String>,
     /// Package name
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<crate::models::ast::Identifier>,
+    pub name: Identifier,
 }
 
 impl PackageClause {
     /// Defines a package identifier
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: Identifier::new(name),
+        }
     }
 }
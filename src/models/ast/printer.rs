@@ -0,0 +1,501 @@
+//! Printer
+//!
+//! Formats AST nodes back into Flux source.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use crate::models::ast::{
+    Assignment, CallExpression, Expression, FunctionBody, Literal, Package, Property, PropertyKey,
+    Statement, StringExpressionPart,
+};
+use crate::models::File;
+
+const INDENT: &str = "    ";
+
+impl Display for Package {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, file) in self.files.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n\n")?;
+            }
+            write!(f, "{}", file)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for File {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut sections = vec![];
+        if let Some(package) = &self.package {
+            sections.push(format!("package {}", package.name.name));
+        }
+        if !self.imports.is_empty() {
+            let mut imports = String::new();
+            for (i, import) in self.imports.iter().enumerate() {
+                if i > 0 {
+                    imports.push('\n');
+                }
+                imports.push_str("import ");
+                if let Some(alias) = &import.r#as {
+                    write!(imports, "{} ", alias.name)?;
+                }
+                write_string(&mut imports, &import.path.value);
+            }
+            sections.push(imports);
+        }
+        if !self.body.is_empty() {
+            let mut body = String::new();
+            write_statements(&mut body, &self.body, 0)?;
+            sections.push(body);
+        }
+        f.write_str(&sections.join("\n\n"))
+    }
+}
+
+impl Display for Statement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        write_statement(&mut out, self, 0)?;
+        f.write_str(&out)
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        write_expression(&mut out, self, 0)?;
+        f.write_str(&out)
+    }
+}
+
+fn write_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str(INDENT);
+    }
+}
+
+fn write_statements(out: &mut String, statements: &[Statement], indent: usize) -> fmt::Result {
+    for (i, statement) in statements.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        write_indent(out, indent);
+        write_statement(out, statement, indent)?;
+    }
+    Ok(())
+}
+
+fn write_statement(out: &mut String, statement: &Statement, indent: usize) -> fmt::Result {
+    match statement {
+        Statement::Expression(statement) => write_expression(out, &statement.expression, indent),
+        Statement::Variable(assignment) => {
+            write!(out, "{} = ", assignment.id.name)?;
+            write_expression(out, &assignment.init, indent)
+        }
+        Statement::Member(assignment) => {
+            write_expression(out, &Expression::Member(assignment.member.clone()), indent)?;
+            out.push_str(" = ");
+            write_expression(out, &assignment.init, indent)
+        }
+        Statement::Option(option) => {
+            out.push_str("option ");
+            match &option.assignment {
+                Assignment::Variable(assignment) => {
+                    write_statement(out, &Statement::Variable(assignment.clone()), indent)
+                }
+                Assignment::Member(assignment) => {
+                    write_statement(out, &Statement::Member(assignment.clone()), indent)
+                }
+            }
+        }
+        Statement::Return(statement) => {
+            out.push_str("return ");
+            write_expression(out, &statement.argument, indent)
+        }
+        Statement::Builtin(statement) => write!(out, "builtin {}", statement.id.name),
+        Statement::Test(statement) => {
+            out.push_str("test ");
+            write_statement(
+                out,
+                &Statement::Variable(statement.assignment.clone()),
+                indent,
+            )
+        }
+        Statement::TestCase(statement) => {
+            write!(out, "testcase {} ", statement.id.name)?;
+            if let Some(extends) = &statement.extends {
+                out.push_str("extends ");
+                write_string(out, &extends.value);
+                out.push(' ');
+            }
+            out.push_str("{\n");
+            write_statements(out, &statement.block.body, indent + 1)?;
+            out.push('\n');
+            write_indent(out, indent);
+            out.push('}');
+            Ok(())
+        }
+        Statement::Bad(statement) => {
+            out.push_str(&statement.text);
+            Ok(())
+        }
+    }
+}
+
+/// Binding strength of an expression, operands binding looser than their
+/// parent are parenthesized
+fn precedence(expression: &Expression) -> u8 {
+    match expression {
+        Expression::Function(_) | Expression::Conditional(_) => 0,
+        Expression::Logical(logical) if logical.operator == "or" => 1,
+        Expression::Logical(_) => 2,
+        Expression::Unary(unary) if unary.operator == "not" || unary.operator == "exists" => 3,
+        Expression::Binary(binary) => match binary.operator.as_str() {
+            "+" | "-" => 5,
+            "*" | "/" | "%" => 6,
+            "^" => 7,
+            _ => 4,
+        },
+        Expression::Unary(_) => 8,
+        Expression::Pipe(_) => 9,
+        _ => 10,
+    }
+}
+
+fn write_operand(out: &mut String, operand: &Expression, parent: u8, indent: usize) -> fmt::Result {
+    if precedence(operand) < parent {
+        out.push('(');
+        write_expression(out, operand, indent)?;
+        out.push(')');
+        Ok(())
+    } else {
+        write_expression(out, operand, indent)
+    }
+}
+
+fn write_expression(out: &mut String, expression: &Expression, indent: usize) -> fmt::Result {
+    let prec = precedence(expression);
+    match expression {
+        Expression::Identifier(identifier) => out.push_str(&identifier.name),
+        Expression::Literal(literal) => write_literal(out, literal)?,
+        Expression::Member(member) => {
+            write_operand(out, &member.object, prec, indent)?;
+            match &member.property {
+                PropertyKey::Identifier(identifier) => write!(out, ".{}", identifier.name)?,
+                PropertyKey::String(literal) => {
+                    out.push('[');
+                    write_string(out, &literal.value);
+                    out.push(']');
+                }
+            }
+        }
+        Expression::Index(index) => {
+            write_operand(out, &index.array, prec, indent)?;
+            out.push('[');
+            write_expression(out, &index.index, indent)?;
+            out.push(']');
+        }
+        Expression::Call(call) => write_call(out, call, indent)?,
+        Expression::Pipe(pipe) => {
+            write_operand(out, &pipe.argument, prec, indent)?;
+            out.push('\n');
+            write_indent(out, indent + 1);
+            out.push_str("|> ");
+            write_call(out, &pipe.call, indent)?;
+        }
+        Expression::Object(object) => {
+            out.push('{');
+            if let Some(with) = &object.with {
+                write!(out, "{} with ", with.name)?;
+            }
+            write_properties(out, &object.properties, ": ", indent)?;
+            out.push('}');
+        }
+        Expression::Array(array) => {
+            out.push('[');
+            for (i, element) in array.elements.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_expression(out, element, indent)?;
+            }
+            out.push(']');
+        }
+        Expression::Dict(dict) => {
+            out.push('[');
+            if dict.elements.is_empty() {
+                out.push(':');
+            }
+            for (i, item) in dict.elements.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_expression(out, &item.key, indent)?;
+                out.push_str(": ");
+                write_expression(out, &item.val, indent)?;
+            }
+            out.push(']');
+        }
+        Expression::Function(function) => {
+            out.push('(');
+            write_properties(out, &function.params, "=", indent)?;
+            out.push_str(") => ");
+            match &function.body {
+                FunctionBody::Block(block) => {
+                    out.push_str("{\n");
+                    write_statements(out, &block.body, indent + 1)?;
+                    out.push('\n');
+                    write_indent(out, indent);
+                    out.push('}');
+                }
+                FunctionBody::Expression(body) => write_expression(out, body, indent)?,
+            }
+        }
+        Expression::Binary(binary) => {
+            write_operand(out, &binary.left, prec, indent)?;
+            write!(out, " {} ", binary.operator)?;
+            write_operand(out, &binary.right, prec + 1, indent)?;
+        }
+        Expression::Logical(logical) => {
+            write_operand(out, &logical.left, prec, indent)?;
+            write!(out, " {} ", logical.operator)?;
+            write_operand(out, &logical.right, prec + 1, indent)?;
+        }
+        Expression::Unary(unary) => {
+            out.push_str(&unary.operator);
+            if unary.operator.chars().all(char::is_alphabetic) {
+                out.push(' ');
+            }
+            write_operand(out, &unary.argument, prec, indent)?;
+        }
+        Expression::Conditional(conditional) => {
+            out.push_str("if ");
+            write_operand(out, &conditional.test, 1, indent)?;
+            out.push_str(" then ");
+            write_operand(out, &conditional.consequent, 1, indent)?;
+            out.push_str(" else ");
+            write_operand(out, &conditional.alternate, 1, indent)?;
+        }
+        Expression::Paren(paren) => {
+            out.push('(');
+            write_expression(out, &paren.expression, indent)?;
+            out.push(')');
+        }
+        Expression::String(string) => {
+            out.push('"');
+            for part in &string.parts {
+                match part {
+                    StringExpressionPart::Text { value } => write_escaped(out, value),
+                    StringExpressionPart::Interpolated { expression } => {
+                        out.push_str("${");
+                        write_expression(out, expression, indent)?;
+                        out.push('}');
+                    }
+                }
+            }
+            out.push('"');
+        }
+        Expression::Bad(bad) => out.push_str(&bad.text),
+    }
+    Ok(())
+}
+
+fn write_literal(out: &mut String, literal: &Literal) -> fmt::Result {
+    match literal {
+        Literal::Boolean(boolean) => write!(out, "{}", boolean.value)?,
+        Literal::DateTime(date_time) => out.push_str(&date_time.value),
+        Literal::Duration(duration) => {
+            for value in &duration.values {
+                write!(out, "{}{}", value.magnitude, value.unit)?;
+            }
+        }
+        // Flux has no literal for NaN and infinities, they are converted
+        // from strings instead
+        Literal::Float(float) if float.value.is_nan() => out.push_str(r#"float(v: "NaN")"#),
+        Literal::Float(float) if float.value.is_infinite() => {
+            let sign = if float.value > 0.0 { '+' } else { '-' };
+            write!(out, r#"float(v: "{}Inf")"#, sign)?
+        }
+        Literal::Float(float) => {
+            let value = float.value.to_string();
+            out.push_str(&value);
+            if !value.contains(|c: char| !c.is_ascii_digit() && c != '-') {
+                out.push_str(".0");
+            }
+        }
+        Literal::Integer(integer) => out.push_str(&integer.value),
+        Literal::Pipe => out.push_str("<-"),
+        Literal::Regexp(regexp) => write!(out, "/{}/", regexp.value.replace('/', "\\/"))?,
+        Literal::String(string) => write_string(out, &string.value),
+        Literal::UnsignedInteger(unsigned) => out.push_str(&unsigned.value),
+        Literal::Label(label) => write!(out, ".{}", label.value)?,
+    }
+    Ok(())
+}
+
+fn write_call(out: &mut String, call: &CallExpression, indent: usize) -> fmt::Result {
+    write_operand(out, &call.callee, 10, indent)?;
+    out.push('(');
+    for (i, argument) in call.arguments.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        // Arguments are passed as a single object without braces
+        match argument {
+            Expression::Object(object) if object.with.is_none() => {
+                write_properties(out, &object.properties, ": ", indent)?
+            }
+            argument => write_expression(out, argument, indent)?,
+        }
+    }
+    out.push(')');
+    Ok(())
+}
+
+fn write_properties(
+    out: &mut String,
+    properties: &[Property],
+    separator: &str,
+    indent: usize,
+) -> fmt::Result {
+    for (i, property) in properties.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        match &property.key {
+            PropertyKey::Identifier(identifier) => out.push_str(&identifier.name),
+            PropertyKey::String(literal) => write_string(out, &literal.value),
+        }
+        if let Some(value) = &property.value {
+            out.push_str(separator);
+            write_expression(out, value, indent)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+    write_escaped(out, value);
+    out.push('"');
+}
+
+fn write_escaped(out: &mut String, value: &str) {
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '$' if chars.peek() == Some(&'{') => out.push_str("\\$"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ast::ImportDeclaration;
+
+    #[test]
+    fn print_file() {
+        let r = || Expression::identifier("r");
+        let file = File {
+            imports: vec![ImportDeclaration::new("strings")],
+            body: vec![
+                Statement::option(
+                    "now",
+                    Expression::function(
+                        &[],
+                        Expression::date_time(
+                            chrono::DateTime::parse_from_rfc3339("2023-01-01T00:00:00Z").unwrap(),
+                        ),
+                    ),
+                ),
+                Statement::expression(Expression::pipe(
+                    Expression::pipe(
+                        Expression::call(
+                            Expression::identifier("from"),
+                            vec![Property::with_value("bucket", Expression::string("b\"1"))],
+                        ),
+                        Expression::identifier("range"),
+                        vec![Property::with_value("start", Expression::duration(-1, "h"))],
+                    ),
+                    Expression::identifier("filter"),
+                    vec![Property::with_value(
+                        "fn",
+                        Expression::function(
+                            &["r"],
+                            Expression::logical(
+                                "and",
+                                Expression::binary(
+                                    "==",
+                                    Expression::member(r(), "tenant"),
+                                    Expression::string("a"),
+                                ),
+                                Expression::logical(
+                                    "or",
+                                    Expression::binary(
+                                        ">",
+                                        Expression::member(r(), "_value"),
+                                        Expression::float(1.0),
+                                    ),
+                                    Expression::unary("not", Expression::boolean(true)),
+                                ),
+                            ),
+                        ),
+                    )],
+                )),
+            ],
+            ..File::default()
+        };
+
+        assert_eq!(
+            file.to_string(),
+            r#"import "strings"
+
+option now = () => 2023-01-01T00:00:00Z
+from(bucket: "b\"1")
+    |> range(start: -1h)
+    |> filter(fn: (r) => r.tenant == "a" and (r._value > 1.0 or not true))"#
+        );
+    }
+
+    #[test]
+    fn print_parsed_ast() {
+        let json = r#"{"type":"File","body":[{"type":"ExpressionStatement","expression":{"type":"CallExpression","callee":{"type":"Identifier","name":"limit"},"arguments":[{"type":"ObjectExpression","properties":[{"type":"Property","key":{"type":"Identifier","name":"n"},"value":{"type":"IntegerLiteral","value":"10"}}]}]}}]}"#;
+        let file: File = serde_json::from_str(json).unwrap();
+
+        assert_eq!(file.to_string(), "limit(n: 10)");
+    }
+    #[test]
+    fn print_test_case() {
+        let json = r#"{"type":"File","body":[{"type":"TestCaseStatement","id":{"type":"Identifier","name":"sorted"},"block":{"type":"Block","body":[{"type":"ExpressionStatement","expression":{"type":"CallExpression","callee":{"type":"Identifier","name":"sort"},"arguments":[{"type":"ObjectExpression","properties":[{"type":"Property","key":{"type":"Identifier","name":"columns"},"value":{"type":"LabelLiteral","value":"host"}}]}]}},{"type":"ExpressionStatement","expression":{"type":"BadExpression","text":"@"}}]}}]}"#;
+        let file: File = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            file.to_string(),
+            "testcase sorted {\n    sort(columns: .host)\n    @\n}"
+        );
+    }
+    #[test]
+    fn print_non_finite_floats() {
+        assert_eq!(
+            Expression::float(f64::NAN).to_string(),
+            r#"float(v: "NaN")"#
+        );
+        assert_eq!(
+            Expression::float(f64::INFINITY).to_string(),
+            r#"float(v: "+Inf")"#
+        );
+        assert_eq!(
+            Expression::float(f64::NEG_INFINITY).to_string(),
+            r#"float(v: "-Inf")"#
+        );
+        assert_eq!(Expression::float(-2.0).to_string(), "-2.0");
+    }
+}
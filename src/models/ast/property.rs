@@ -2,23 +2,32 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::models::ast::{Expression, PropertyKey};
+
 /// The value associated with a key
-#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Property {
-    /// Type of AST node
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub r#type: Option<String>,
     /// Property Key
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub key: Option<crate::models::ast::PropertyKey>,
-    /// Property Value
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub value: Option<crate::models::ast::Expression>,
+    pub key: PropertyKey,
+    /// Property Value, missing for function parameters without a default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Expression>,
 }
 
 impl Property {
-    /// The value associated with a key
-    pub fn new() -> Self {
-        Self::default()
+    /// Property `name: value`
+    pub fn with_value(name: &str, value: Expression) -> Self {
+        Self {
+            key: PropertyKey::identifier(name),
+            value: Some(value),
+        }
+    }
+
+    /// Function parameter `name` without a default
+    pub fn param(name: &str) -> Self {
+        Self {
+            key: PropertyKey::identifier(name),
+            value: None,
+        }
     }
 }
@@ -2,23 +2,35 @@
 
 use serde::{Deserialize, Serialize};
 
-/// Key value pair
-#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
-pub struct PropertyKey {
-    /// Type of AST node
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub r#type: Option<String>,
-    /// PropertyKey name
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-    /// PropertyKey value
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub value: Option<String>,
+use crate::models::ast::{Identifier, StringLiteral};
+
+/// Key of a property
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum PropertyKey {
+    /// Key written as an identifier, e.g. `start`
+    Identifier(Identifier),
+    /// Key written as a string, e.g. `"host name"`
+    #[serde(rename = "StringLiteral")]
+    String(StringLiteral),
 }
 
 impl PropertyKey {
-    /// Returns an instance of PropertyKey
-    pub fn new() -> Self {
-        Self::default()
+    /// Key written as an identifier, e.g. `start`
+    pub fn identifier(name: &str) -> Self {
+        Self::Identifier(Identifier::new(name))
+    }
+
+    /// Key written as a string, e.g. `"host name"`
+    pub fn string(value: &str) -> Self {
+        Self::String(StringLiteral::new(value))
+    }
+
+    /// Name of the key, whichever way it is written
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Identifier(identifier) => &identifier.name,
+            Self::String(literal) => &literal.value,
+        }
     }
 }
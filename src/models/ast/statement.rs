@@ -2,38 +2,127 @@
 
 use serde::{Deserialize, Serialize};
 
-/// Expression Statement
-#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
-pub struct Statement {
-    /// Type of AST node
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub r#type: Option<String>,
-    /// Raw source text
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub text: Option<String>,
-    /// Statement identitfier
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub id: Option<crate::models::ast::Identifier>,
-    /// Initial Value
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub init: Option<crate::models::ast::Expression>,
-    /// Member
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub member: Option<crate::models::ast::MemberExpression>,
-    /// Expression
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub expression: Option<crate::models::ast::Expression>,
-    /// Argument
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub argument: Option<crate::models::ast::Expression>,
-    /// Assignment
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub assignment: Option<crate::models::ast::VariableAssignment>,
+use crate::models::ast::{
+    Block, Expression, Identifier, MemberAssignment, StringLiteral, VariableAssignment,
+};
+
+/// Statement AST
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Statement {
+    /// Expression evaluated for its result, e.g. a pipeline
+    #[serde(rename = "ExpressionStatement")]
+    Expression(ExpressionStatement),
+    /// Variable assignment, e.g. `x = 1`
+    #[serde(rename = "VariableAssignment")]
+    Variable(VariableAssignment),
+    /// Member assignment, e.g. `o.x = 1`
+    #[serde(rename = "MemberAssignment")]
+    Member(MemberAssignment),
+    /// Option assignment, e.g. `option now = () => 2023-01-01T00:00:00Z`
+    #[serde(rename = "OptionStatement")]
+    Option(OptionStatement),
+    /// Return from a function block
+    #[serde(rename = "ReturnStatement")]
+    Return(ReturnStatement),
+    /// Declaration of a builtin value
+    #[serde(rename = "BuiltinStatement")]
+    Builtin(BuiltinStatement),
+    /// Test definition, e.g. `test t = () => ({input: a, want: b})`
+    #[serde(rename = "TestStatement")]
+    Test(TestStatement),
+    /// Test case, e.g. `testcase t { ... }`
+    #[serde(rename = "TestCaseStatement")]
+    TestCase(TestCaseStatement),
+    /// Source that could not be parsed
+    #[serde(rename = "BadStatement")]
+    Bad(BadStatement),
 }
 
 impl Statement {
-    /// Returns an instance of Statement
-    pub fn new() -> Self {
-        Self::default()
+    /// Variable assignment, e.g. `x = 1`
+    pub fn variable(name: &str, init: Expression) -> Self {
+        Self::Variable(VariableAssignment::new(name, init))
+    }
+
+    /// Option assignment, e.g. `option now = () => 2023-01-01T00:00:00Z`
+    pub fn option(name: &str, init: Expression) -> Self {
+        Self::Option(OptionStatement {
+            assignment: Assignment::Variable(VariableAssignment::new(name, init)),
+        })
     }
+
+    /// Expression evaluated for its result, e.g. a pipeline
+    pub fn expression(expression: Expression) -> Self {
+        Self::Expression(ExpressionStatement { expression })
+    }
+}
+
+/// May consist of an expression that does not return a value and is executed
+/// solely for its side-effects
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExpressionStatement {
+    /// Expression
+    pub expression: Expression,
+}
+
+/// A single variable declaration
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OptionStatement {
+    /// Assignment
+    pub assignment: Assignment,
+}
+
+/// Assignment of an option
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Assignment {
+    /// Option variable, e.g. `option now = ...`
+    #[serde(rename = "VariableAssignment")]
+    Variable(VariableAssignment),
+    /// Option of a package, e.g. `option profiler.enabledProfilers = ...`
+    #[serde(rename = "MemberAssignment")]
+    Member(MemberAssignment),
+}
+
+/// Defines an Expression to return
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReturnStatement {
+    /// Argument
+    pub argument: Expression,
+}
+
+/// Declares a builtin identifier and type
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BuiltinStatement {
+    /// Builtin identifier
+    pub id: Identifier,
+}
+
+/// Declares a Flux test case
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestStatement {
+    /// Test definition
+    pub assignment: VariableAssignment,
+}
+
+/// Defines a test case, run by `flux test`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TestCaseStatement {
+    /// Test case name
+    pub id: Identifier,
+    /// Test case extended by this one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<StringLiteral>,
+    /// Test case body
+    pub block: Block,
+}
+
+/// A placeholder for statements for which no correct statement nodes can be
+/// created
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct BadStatement {
+    /// Raw source text
+    #[serde(default)]
+    pub text: String,
 }
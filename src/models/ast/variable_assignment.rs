@@ -2,23 +2,39 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::models::ast::{Expression, Identifier, MemberExpression};
+
 /// Represents the declaration of a variable
-#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct VariableAssignment {
-    /// Type of AST node
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
-    pub r#type: Option<String>,
     /// Variable Identifier
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub id: Option<crate::models::ast::Identifier>,
+    pub id: Identifier,
     /// Variable initial value
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub init: Option<crate::models::ast::Expression>,
+    pub init: Expression,
 }
 
 impl VariableAssignment {
     /// Represents the declaration of a variable
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(name: &str, init: Expression) -> Self {
+        Self {
+            id: Identifier::new(name),
+            init,
+        }
+    }
+}
+
+/// Represents the assignment of a member of an object
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemberAssignment {
+    /// Member being assigned
+    pub member: MemberExpression,
+    /// Assigned value
+    pub init: Expression,
+}
+
+impl MemberAssignment {
+    /// Represents the assignment of a member of an object
+    pub fn new(member: MemberExpression, init: Expression) -> Self {
+        Self { member, init }
     }
 }
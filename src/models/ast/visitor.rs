@@ -0,0 +1,285 @@
+//! Visitor
+//!
+//! Walk a Flux AST, e.g. to lint the Flux of tasks.
+
+use crate::models::ast::{
+    Assignment, CallExpression, Expression, FunctionBody, Identifier, Literal, OptionStatement,
+    Package, Property, Statement, StringExpressionPart,
+};
+use crate::models::File;
+
+/// Visits the nodes of an AST.
+///
+/// Every method walks into the children of its node by default. Override
+/// the ones of interest and call the matching `walk_*` function to keep
+/// descending.
+///
+/// ```
+/// use influxdb2::models::ast::{visitor, CallExpression, Expression, Package, Visitor};
+///
+/// /// Collects the names of the called functions
+/// struct Calls(Vec<String>);
+///
+/// impl Visitor for Calls {
+///     fn visit_call(&mut self, call: &CallExpression) {
+///         if let Expression::Identifier(id) = call.callee.as_ref() {
+///             self.0.push(id.name.clone());
+///         }
+///         visitor::walk_call(self, call);
+///     }
+/// }
+///
+/// # fn lint(ast: &Package) -> Vec<String> {
+/// let mut calls = Calls(vec![]);
+/// calls.visit_package(ast);
+/// calls.0
+/// # }
+/// ```
+pub trait Visitor {
+    /// Visit a package
+    fn visit_package(&mut self, package: &Package) {
+        walk_package(self, package)
+    }
+
+    /// Visit a file
+    fn visit_file(&mut self, file: &File) {
+        walk_file(self, file)
+    }
+
+    /// Visit a statement
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement)
+    }
+
+    /// Visit an expression
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression)
+    }
+
+    /// Visit a function call, including the call of a pipe expression
+    fn visit_call(&mut self, call: &CallExpression) {
+        walk_call(self, call)
+    }
+
+    /// Visit a property of an object or a function parameter
+    fn visit_property(&mut self, property: &Property) {
+        walk_property(self, property)
+    }
+
+    /// Visit an identifier, either referenced or assigned
+    fn visit_identifier(&mut self, _identifier: &Identifier) {}
+
+    /// Visit a literal
+    fn visit_literal(&mut self, _literal: &Literal) {}
+}
+
+/// Visit the files of a package
+pub fn walk_package<V: Visitor + ?Sized>(visitor: &mut V, package: &Package) {
+    for file in &package.files {
+        visitor.visit_file(file);
+    }
+}
+
+/// Visit the statements of a file
+pub fn walk_file<V: Visitor + ?Sized>(visitor: &mut V, file: &File) {
+    for statement in &file.body {
+        visitor.visit_statement(statement);
+    }
+}
+
+/// Visit the children of a statement
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::Expression(statement) => visitor.visit_expression(&statement.expression),
+        Statement::Variable(assignment)
+        | Statement::Option(OptionStatement {
+            assignment: Assignment::Variable(assignment),
+        }) => {
+            visitor.visit_identifier(&assignment.id);
+            visitor.visit_expression(&assignment.init);
+        }
+        Statement::Member(assignment)
+        | Statement::Option(OptionStatement {
+            assignment: Assignment::Member(assignment),
+        }) => {
+            visitor.visit_expression(&assignment.member.object);
+            visitor.visit_expression(&assignment.init);
+        }
+        Statement::Return(statement) => visitor.visit_expression(&statement.argument),
+        Statement::Builtin(statement) => visitor.visit_identifier(&statement.id),
+        Statement::Test(statement) => {
+            visitor.visit_identifier(&statement.assignment.id);
+            visitor.visit_expression(&statement.assignment.init);
+        }
+        Statement::TestCase(statement) => {
+            visitor.visit_identifier(&statement.id);
+            for statement in &statement.block.body {
+                visitor.visit_statement(statement);
+            }
+        }
+        Statement::Bad(_) => {}
+    }
+}
+
+/// Visit the children of an expression
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expression: &Expression) {
+    match expression {
+        Expression::Identifier(identifier) => visitor.visit_identifier(identifier),
+        Expression::Literal(literal) => visitor.visit_literal(literal),
+        Expression::Array(array) => {
+            for element in &array.elements {
+                visitor.visit_expression(element);
+            }
+        }
+        Expression::Dict(dict) => {
+            for item in &dict.elements {
+                visitor.visit_expression(&item.key);
+                visitor.visit_expression(&item.val);
+            }
+        }
+        Expression::Function(function) => {
+            for param in &function.params {
+                visitor.visit_property(param);
+            }
+            match &function.body {
+                FunctionBody::Block(block) => {
+                    for statement in &block.body {
+                        visitor.visit_statement(statement);
+                    }
+                }
+                FunctionBody::Expression(body) => visitor.visit_expression(body),
+            }
+        }
+        Expression::Binary(binary) => {
+            visitor.visit_expression(&binary.left);
+            visitor.visit_expression(&binary.right);
+        }
+        Expression::Logical(logical) => {
+            visitor.visit_expression(&logical.left);
+            visitor.visit_expression(&logical.right);
+        }
+        Expression::Unary(unary) => visitor.visit_expression(&unary.argument),
+        Expression::Call(call) => visitor.visit_call(call),
+        Expression::Pipe(pipe) => {
+            visitor.visit_expression(&pipe.argument);
+            visitor.visit_call(&pipe.call);
+        }
+        Expression::Member(member) => visitor.visit_expression(&member.object),
+        Expression::Index(index) => {
+            visitor.visit_expression(&index.array);
+            visitor.visit_expression(&index.index);
+        }
+        Expression::Object(object) => {
+            if let Some(with) = &object.with {
+                visitor.visit_identifier(with);
+            }
+            for property in &object.properties {
+                visitor.visit_property(property);
+            }
+        }
+        Expression::Conditional(conditional) => {
+            visitor.visit_expression(&conditional.test);
+            visitor.visit_expression(&conditional.consequent);
+            visitor.visit_expression(&conditional.alternate);
+        }
+        Expression::Paren(paren) => visitor.visit_expression(&paren.expression),
+        Expression::String(string) => {
+            for part in &string.parts {
+                if let StringExpressionPart::Interpolated { expression } = part {
+                    visitor.visit_expression(expression);
+                }
+            }
+        }
+        Expression::Bad(bad) => {
+            if let Some(expression) = &bad.expression {
+                visitor.visit_expression(expression);
+            }
+        }
+    }
+}
+
+/// Visit the callee and the arguments of a call
+pub fn walk_call<V: Visitor + ?Sized>(visitor: &mut V, call: &CallExpression) {
+    visitor.visit_expression(&call.callee);
+    for argument in &call.arguments {
+        visitor.visit_expression(argument);
+    }
+}
+
+/// Visit the value of a property
+pub fn walk_property<V: Visitor + ?Sized>(visitor: &mut V, property: &Property) {
+    if let Some(value) = &property.value {
+        visitor.visit_expression(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flags `from()` pipelines that are not bounded by `range()`
+    #[derive(Default)]
+    struct UnboundedFrom {
+        ranged: bool,
+        unbounded: usize,
+    }
+
+    impl Visitor for UnboundedFrom {
+        fn visit_expression(&mut self, expression: &Expression) {
+            if let Expression::Pipe(pipe) = expression {
+                if is_call_to(&pipe.call, "range") {
+                    self.ranged = true;
+                }
+            }
+            walk_expression(self, expression);
+        }
+
+        fn visit_call(&mut self, call: &CallExpression) {
+            if is_call_to(call, "from") && !std::mem::take(&mut self.ranged) {
+                self.unbounded += 1;
+            }
+            walk_call(self, call);
+        }
+    }
+
+    fn is_call_to(call: &CallExpression, name: &str) -> bool {
+        matches!(call.callee.as_ref(), Expression::Identifier(id) if id.name == name)
+    }
+
+    #[test]
+    fn visit_package() {
+        let from = || {
+            Expression::call(
+                Expression::identifier("from"),
+                vec![crate::models::ast::Property::with_value(
+                    "bucket",
+                    Expression::string("b"),
+                )],
+            )
+        };
+        let file = File {
+            body: vec![
+                Statement::variable(
+                    "bounded",
+                    Expression::pipe(from(), Expression::identifier("range"), vec![]),
+                ),
+                Statement::expression(Expression::pipe(
+                    from(),
+                    Expression::identifier("limit"),
+                    vec![],
+                )),
+            ],
+            ..File::default()
+        };
+        let package: Package = serde_json::from_value(serde_json::json!({
+            "type": "Package",
+            "files": [serde_json::to_value(&file).unwrap()],
+        }))
+        .unwrap();
+
+        let mut lint = UnboundedFrom::default();
+        lint.visit_package(&package);
+
+        assert_eq!(lint.unbounded, 1);
+    }
+}
@@ -0,0 +1,215 @@
+//! Backup
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// BackupManifest : Describes the contents of a backup archive, i.e. which
+/// metadata snapshots, buckets and shards went into it.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    /// Snapshot of the KV store
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kv: Option<ManifestFileEntry>,
+    /// Snapshot of the SQL store
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sql: Option<ManifestFileEntry>,
+    /// Buckets included in the backup
+    #[serde(default)]
+    pub buckets: Vec<BucketMetadataManifest>,
+    /// Shard files included in the backup
+    #[serde(default)]
+    pub files: Vec<ManifestFileEntry>,
+}
+
+impl BackupManifest {
+    /// Returns an empty BackupManifest
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// IDs of every shard referenced by the buckets in this manifest
+    pub fn shard_ids(&self) -> Vec<u64> {
+        self.buckets
+            .iter()
+            .flat_map(|b| b.retention_policies.iter())
+            .flat_map(|rp| rp.shard_groups.iter())
+            .flat_map(|sg| sg.shards.iter())
+            .map(|s| s.id)
+            .collect()
+    }
+}
+
+/// A single file stored in a backup archive
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestFileEntry {
+    /// File name, relative to the archive root
+    pub file_name: String,
+    /// File creation time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// File size in bytes
+    pub size: u64,
+    /// Compression applied to the file
+    pub compression: FileCompression,
+}
+
+impl ManifestFileEntry {
+    /// Returns instance of ManifestFileEntry
+    pub fn new(file_name: String, size: u64, compression: FileCompression) -> Self {
+        Self {
+            file_name,
+            created_at: None,
+            size,
+            compression,
+        }
+    }
+}
+
+/// Compression applied to a file in a backup archive
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileCompression {
+    /// Uncompressed
+    None,
+    /// Gzip
+    Gzip,
+}
+
+/// Bucket metadata stored in a backup manifest
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BucketMetadataManifest {
+    /// Organization ID
+    #[serde(rename = "organizationID")]
+    pub organization_id: String,
+    /// Organization name
+    pub organization_name: String,
+    /// Bucket ID
+    #[serde(rename = "bucketID")]
+    pub bucket_id: String,
+    /// Bucket name
+    pub bucket_name: String,
+    /// Bucket description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Name of the default retention policy
+    pub default_retention_policy: String,
+    /// Retention policies of the bucket
+    #[serde(default)]
+    pub retention_policies: Vec<RetentionPolicyManifest>,
+}
+
+/// Retention policy stored in a backup manifest
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicyManifest {
+    /// Retention policy name
+    pub name: String,
+    /// Replication factor
+    pub replica_n: i64,
+    /// Retention duration in nanoseconds
+    pub duration: i64,
+    /// Shard group duration in nanoseconds
+    pub shard_group_duration: i64,
+    /// Shard groups of the retention policy
+    #[serde(default)]
+    pub shard_groups: Vec<ShardGroupManifest>,
+    /// Subscriptions of the retention policy
+    #[serde(default)]
+    pub subscriptions: Vec<SubscriptionManifest>,
+}
+
+/// Shard group stored in a backup manifest
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardGroupManifest {
+    /// Shard group ID
+    pub id: u64,
+    /// Start of the time range covered by the shard group
+    pub start_time: DateTime<Utc>,
+    /// End of the time range covered by the shard group
+    pub end_time: DateTime<Utc>,
+    /// Deletion time, if the shard group was deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Truncation time, if the shard group was truncated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncated_at: Option<DateTime<Utc>>,
+    /// Shards in the shard group
+    #[serde(default)]
+    pub shards: Vec<ShardManifest>,
+}
+
+/// Shard stored in a backup manifest
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardManifest {
+    /// Shard ID
+    pub id: u64,
+    /// Nodes owning the shard
+    #[serde(default)]
+    pub shard_owners: Vec<ShardOwner>,
+}
+
+/// Owner of a shard
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardOwner {
+    /// ID of the node that owns the shard
+    #[serde(rename = "nodeID")]
+    pub node_id: u64,
+}
+
+/// Subscription stored in a backup manifest
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionManifest {
+    /// Subscription name
+    pub name: String,
+    /// Subscription mode
+    pub mode: String,
+    /// Subscription destinations
+    #[serde(default)]
+    pub destinations: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_manifest() {
+        let manifest: BackupManifest = serde_json::from_str(
+            r#"{
+                "kv": {"fileName": "kv.gz", "size": 10, "compression": "gzip"},
+                "buckets": [{
+                    "organizationID": "o1",
+                    "organizationName": "org",
+                    "bucketID": "b1",
+                    "bucketName": "bucket",
+                    "defaultRetentionPolicy": "autogen",
+                    "retentionPolicies": [{
+                        "name": "autogen",
+                        "replicaN": 1,
+                        "duration": 0,
+                        "shardGroupDuration": 604800000000000,
+                        "shardGroups": [{
+                            "id": 1,
+                            "startTime": "2022-01-03T00:00:00Z",
+                            "endTime": "2022-01-10T00:00:00Z",
+                            "shards": [{"id": 7, "shardOwners": []}, {"id": 8}]
+                        }]
+                    }]
+                }],
+                "files": [{"fileName": "7.tar.gz", "size": 42, "compression": "gzip"}]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.shard_ids(), vec![7, 8]);
+        assert_eq!(manifest.files[0].compression, FileCompression::Gzip);
+        assert!(manifest.sql.is_none());
+    }
+}
@@ -0,0 +1,22 @@
+//! Server configuration
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Config : Runtime configuration of an InfluxDB instance
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct Config {
+    /// Configuration options, keyed by option name
+    #[serde(default)]
+    pub config: HashMap<String, serde_json::Value>,
+}
+
+impl Config {
+    /// Returns an empty Config
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Flags : Feature flags of an InfluxDB instance, keyed by flag name
+pub type Flags = HashMap<String, serde_json::Value>;
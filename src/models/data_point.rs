@@ -16,6 +16,64 @@ pub enum DataPointError {
         /// The current state of the `DataPointBuilder`
         data_point_builder: DataPointBuilder,
     },
+
+    /// Returned when the measurement name is empty.
+    #[snafu(display("The measurement name must not be empty"))]
+    EmptyMeasurement,
+
+    /// Returned when a tag key is empty.
+    #[snafu(display("Tag keys must not be empty"))]
+    EmptyTagKey,
+
+    /// Returned when a tag value is empty.
+    #[snafu(display("The value of the tag `{}` must not be empty", tag_key))]
+    EmptyTagValue {
+        /// The key of the tag
+        tag_key: String,
+    },
+
+    /// Returned when a field key is empty.
+    #[snafu(display("Field keys must not be empty"))]
+    EmptyFieldKey,
+
+    /// Returned when a float field is NaN or infinite, which line protocol
+    /// cannot represent.
+    #[snafu(display("The value of the field `{}` is not finite: {}", field_key, value))]
+    NonFiniteFloat {
+        /// The key of the field
+        field_key: String,
+        /// The value of the field
+        value: f64,
+    },
+
+    /// Returned when the measurement name, a tag key or a field key begins
+    /// with `_`, which is reserved for the system.
+    #[snafu(display("`{}` begins with `_`, which is reserved for the system", name))]
+    ReservedName {
+        /// The offending name
+        name: String,
+    },
+
+    /// Returned when a tag or a field has the key `time`, which is reserved
+    /// for the timestamp.
+    #[snafu(display("`time` is not allowed as a tag or field key"))]
+    TimeKey,
+
+    /// Returned when the measurement name, a tag key, a tag value or a field
+    /// key contains a newline, which line protocol cannot escape.
+    #[snafu(display("`{}` contains a newline", name))]
+    Newline {
+        /// The offending name or tag value
+        name: String,
+    },
+
+    /// Returned when the measurement name, a tag key, a tag value or a field
+    /// key ends with a backslash, which would escape the delimiter after it.
+    #[snafu(display("`{}` ends with a backslash", name))]
+    TrailingBackslash {
+        /// The offending name or tag value
+        name: String,
+    },
 }
 
 /// Incrementally constructs a `DataPoint`.
@@ -28,6 +86,7 @@ pub struct DataPointBuilder {
     tags: BTreeMap<String, String>,
     fields: BTreeMap<String, FieldValue>,
     timestamp: Option<i64>,
+    strict: bool,
 }
 
 impl DataPointBuilder {
@@ -37,6 +96,7 @@ impl DataPointBuilder {
             tags: Default::default(),
             fields: Default::default(),
             timestamp: Default::default(),
+            strict: false,
         }
     }
 
@@ -63,6 +123,12 @@ impl DataPointBuilder {
         self
     }
 
+    /// Makes `build` validate the data point, see `DataPoint::validate`.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
     /// Constructs the data point
     pub fn build(self) -> Result<DataPoint, DataPointError> {
         ensure!(
@@ -77,14 +143,19 @@ impl DataPointBuilder {
             tags,
             fields,
             timestamp,
+            strict,
         } = self;
 
-        Ok(DataPoint {
+        let point = DataPoint {
             measurement,
             tags,
             fields,
             timestamp,
-        })
+        };
+        if strict {
+            point.validate()?;
+        }
+        Ok(point)
     }
 }
 
@@ -106,6 +177,62 @@ impl DataPoint {
     pub fn builder(measurement: impl Into<String>) -> DataPointBuilder {
         DataPointBuilder::new(measurement)
     }
+
+    /// Checks the data point against the restrictions of line protocol, so
+    /// that it is not rejected by the server.
+    pub fn validate(&self) -> Result<(), DataPointError> {
+        fn check_name(name: &str) -> Result<(), DataPointError> {
+            ensure!(!name.starts_with('_'), ReservedName { name });
+            ensure!(name != "time", TimeKey);
+            ensure!(!name.contains('\n'), Newline { name });
+            ensure!(!name.ends_with('\\'), TrailingBackslash { name });
+            Ok(())
+        }
+
+        ensure!(!self.measurement.is_empty(), EmptyMeasurement);
+        ensure!(
+            !self.measurement.starts_with('_'),
+            ReservedName {
+                name: &self.measurement
+            }
+        );
+        ensure!(
+            !self.measurement.contains('\n'),
+            Newline {
+                name: &self.measurement
+            }
+        );
+        ensure!(
+            !self.measurement.ends_with('\\'),
+            TrailingBackslash {
+                name: &self.measurement
+            }
+        );
+
+        for (key, value) in &self.tags {
+            ensure!(!key.is_empty(), EmptyTagKey);
+            check_name(key)?;
+            ensure!(!value.is_empty(), EmptyTagValue { tag_key: key });
+            ensure!(!value.contains('\n'), Newline { name: value });
+            ensure!(!value.ends_with('\\'), TrailingBackslash { name: value });
+        }
+
+        for (key, value) in &self.fields {
+            ensure!(!key.is_empty(), EmptyFieldKey);
+            check_name(key)?;
+            if let FieldValue::F64(value) = *value {
+                ensure!(
+                    value.is_finite(),
+                    NonFiniteFloat {
+                        field_key: key,
+                        value
+                    }
+                );
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl WriteDataPoint for DataPoint {
@@ -283,11 +410,7 @@ impl WriteFieldValue for FieldValue {
             Bool(v) => write!(w, "{}", if *v { "t" } else { "f" }),
             F64(v) => write!(w, "{}", v),
             I64(v) => write!(w, "{}i", v),
-            String(v) => {
-                w.write_all(br#"""#)?;
-                escape_and_write_value(v, FIELD_VALUE_STRING_DELIMITERS, &mut w)?;
-                w.write_all(br#"""#)
-            }
+            String(v) => write_string_field(v, w),
         }
     }
 }
@@ -311,7 +434,40 @@ const MEASUREMENT_DELIMITERS: &[char] = &[',', ' '];
 const TAG_KEY_DELIMITERS: &[char] = &[',', '=', ' '];
 const TAG_VALUE_DELIMITERS: &[char] = TAG_KEY_DELIMITERS;
 const FIELD_KEY_DELIMITERS: &[char] = TAG_KEY_DELIMITERS;
-const FIELD_VALUE_STRING_DELIMITERS: &[char] = &['"'];
+const FIELD_VALUE_STRING_DELIMITERS: &[char] = &['"', '\\'];
+
+/// Writes a measurement name escaped as `DataPoint` does
+pub(crate) fn write_measurement<W: io::Write>(value: &str, w: W) -> io::Result<()> {
+    check_trailing_backslash(value)?;
+    value.write_measurement_to(w)
+}
+
+/// Writes a tag key, a tag value or a field key escaped as `DataPoint`
+/// does, which share the same delimiters
+pub(crate) fn write_key<W: io::Write>(value: &str, w: W) -> io::Result<()> {
+    check_trailing_backslash(value)?;
+    value.write_tag_key_to(w)
+}
+
+/// A trailing backslash would escape the delimiter written after the value
+fn check_trailing_backslash(value: &str) -> io::Result<()> {
+    if value.ends_with('\\') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            DataPointError::TrailingBackslash {
+                name: value.to_owned(),
+            },
+        ));
+    }
+    Ok(())
+}
+
+/// Writes a string field value quoted and escaped as `DataPoint` does
+pub(crate) fn write_string_field<W: io::Write>(value: &str, mut w: W) -> io::Result<()> {
+    w.write_all(br#"""#)?;
+    escape_and_write_value(value, FIELD_VALUE_STRING_DELIMITERS, &mut w)?;
+    w.write_all(br#"""#)
+}
 
 fn escape_and_write_value<W>(
     value: &str,
@@ -329,7 +485,7 @@ where
         last = idx + delim.len();
     }
 
-    w.write_all(value[last..].as_bytes())
+    w.write_all(&value.as_bytes()[last..])
 }
 
 #[cfg(test)]
@@ -400,6 +556,75 @@ mod tests {
         assert!(point_result.is_err());
     }
 
+    #[test]
+    fn valid_point() {
+        let point = DataPoint::builder("m0")
+            .tag("t0", "v0")
+            .field("f0", 1.0)
+            .strict()
+            .build();
+
+        assert!(point.is_ok());
+    }
+
+    #[test]
+    fn invalid_points() {
+        let build = |builder: DataPointBuilder| builder.field("f0", 1.0).strict().build();
+
+        assert!(matches!(
+            build(DataPoint::builder("")),
+            Err(DataPointError::EmptyMeasurement)
+        ));
+        assert!(matches!(
+            build(DataPoint::builder("_m0")),
+            Err(DataPointError::ReservedName { name }) if name == "_m0"
+        ));
+        assert!(matches!(
+            build(DataPoint::builder("m0").tag("", "v0")),
+            Err(DataPointError::EmptyTagKey)
+        ));
+        assert!(matches!(
+            build(DataPoint::builder("m0").tag("t0", "")),
+            Err(DataPointError::EmptyTagValue { tag_key }) if tag_key == "t0"
+        ));
+        assert!(matches!(
+            build(DataPoint::builder("m0").tag("_t0", "v0")),
+            Err(DataPointError::ReservedName { name }) if name == "_t0"
+        ));
+        assert!(matches!(
+            build(DataPoint::builder("m0").tag("time", "v0")),
+            Err(DataPointError::TimeKey)
+        ));
+        assert!(matches!(
+            build(DataPoint::builder("m0").tag("t0", "v\n0")),
+            Err(DataPointError::Newline { .. })
+        ));
+        assert!(matches!(
+            build(DataPoint::builder("m0").tag("t0", "v0\\")),
+            Err(DataPointError::TrailingBackslash { name }) if name == "v0\\"
+        ));
+        assert!(matches!(
+            build(DataPoint::builder("m0").field("f0\\", 1.0)),
+            Err(DataPointError::TrailingBackslash { .. })
+        ));
+        assert!(matches!(
+            build(DataPoint::builder("m0").field("", 1.0)),
+            Err(DataPointError::EmptyFieldKey)
+        ));
+        assert!(matches!(
+            build(DataPoint::builder("m0").field("f1", f64::NAN)),
+            Err(DataPointError::NonFiniteFloat { field_key, .. }) if field_key == "f1"
+        ));
+        assert!(matches!(
+            build(DataPoint::builder("m0").field("f1", f64::INFINITY)),
+            Err(DataPointError::NonFiniteFloat { .. })
+        ));
+
+        // Not validated unless asked for
+        let point = DataPoint::builder("m0").field("f0", f64::NAN).build();
+        assert!(point.unwrap().validate().is_err());
+    }
+
     const ALL_THE_DELIMITERS: &str = r#"alpha,beta=delta gamma"epsilon"#;
 
     #[test]
@@ -444,6 +669,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn backslashes_are_escaped_in_field_values_of_strings() {
+        assert_utf8_strings_eq(
+            &FieldValue::from(r#"C:\dir\"#).field_value_to_vec().unwrap(),
+            br#""C:\\dir\\""#.as_ref(),
+        );
+    }
+
     #[test]
     fn field_value_of_bool() {
         let e = FieldValue::from(true);
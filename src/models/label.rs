@@ -109,3 +109,19 @@ impl Label {
         Self::default()
     }
 }
+
+/// LabelMapping : Attaches a label to a resource
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelMapping {
+    /// Label ID
+    #[serde(rename = "labelID")]
+    pub label_id: String,
+}
+
+impl LabelMapping {
+    /// Returns an instance of LabelMapping
+    pub fn new(label_id: String) -> Self {
+        Self { label_id }
+    }
+}
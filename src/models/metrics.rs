@@ -0,0 +1,132 @@
+//! Metrics
+//!
+//! Typed representation of the Prometheus text exposition format served on
+//! `/metrics`.
+
+use std::collections::BTreeMap;
+
+/// Metrics exposed by an InfluxDB instance
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Metrics {
+    /// Metric families in the order they were exposed
+    pub families: Vec<MetricFamily>,
+}
+
+impl Metrics {
+    /// Returns an empty Metrics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Find a metric family by name
+    pub fn family(&self, name: &str) -> Option<&MetricFamily> {
+        self.families.iter().find(|f| f.name == name)
+    }
+}
+
+/// A group of metrics sharing a name, e.g. all `http_api_requests_total`
+/// series.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricFamily {
+    /// Metric name
+    pub name: String,
+    /// Help text
+    pub help: String,
+    /// Metric type
+    pub r#type: MetricType,
+    /// One metric per label set
+    pub metrics: Vec<Metric>,
+}
+
+impl MetricFamily {
+    /// Returns an empty MetricFamily
+    pub fn new(name: String, r#type: MetricType) -> Self {
+        Self {
+            name,
+            help: String::new(),
+            r#type,
+            metrics: Vec::new(),
+        }
+    }
+}
+
+/// Type of a metric family
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum MetricType {
+    /// Monotonically increasing value
+    Counter,
+    /// Value that can go up and down
+    Gauge,
+    /// Sampled observations counted in configurable buckets
+    Histogram,
+    /// Sampled observations with quantiles over a sliding window
+    Summary,
+    /// Metric without type information
+    Untyped,
+}
+
+/// A single series of a metric family
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metric {
+    /// Labels identifying the series
+    pub labels: BTreeMap<String, String>,
+    /// Series value
+    pub value: MetricValue,
+    /// Optional timestamp in milliseconds since the UNIX epoch
+    pub timestamp: Option<i64>,
+}
+
+/// Value of a single series
+#[derive(Clone, Debug, PartialEq)]
+pub enum MetricValue {
+    /// Counter value
+    Counter(f64),
+    /// Gauge value
+    Gauge(f64),
+    /// Histogram value
+    Histogram(Histogram),
+    /// Summary value
+    Summary(Summary),
+    /// Untyped value
+    Untyped(f64),
+}
+
+/// Histogram value
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Histogram {
+    /// Buckets ordered by upper bound
+    pub buckets: Vec<HistogramBucket>,
+    /// Sum of all observations
+    pub sum: f64,
+    /// Number of observations
+    pub count: u64,
+}
+
+/// Histogram bucket
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HistogramBucket {
+    /// Inclusive upper bound, the `le` label
+    pub upper_bound: f64,
+    /// Number of observations less than or equal to `upper_bound`
+    pub cumulative_count: u64,
+}
+
+/// Summary value
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Summary {
+    /// Quantiles ordered as exposed
+    pub quantiles: Vec<Quantile>,
+    /// Sum of all observations
+    pub sum: f64,
+    /// Number of observations
+    pub count: u64,
+}
+
+/// Summary quantile
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quantile {
+    /// Quantile, the `quantile` label
+    pub quantile: f64,
+    /// Observed value at the quantile
+    pub value: f64,
+}
@@ -17,7 +17,9 @@ pub use self::links::Links;
 pub mod permission;
 pub use self::permission::Permission;
 pub mod label;
-pub use self::label::{Label, LabelCreateRequest, LabelResponse, LabelUpdate, LabelsResponse};
+pub use self::label::{
+    Label, LabelCreateRequest, LabelMapping, LabelResponse, LabelUpdate, LabelsResponse,
+};
 pub mod authorization;
 pub use self::authorization::{Authorization, AuthorizationAllOfLinks};
 pub mod resource;
@@ -27,13 +29,26 @@ pub use self::retention_rule::RetentionRule;
 pub mod query;
 pub use self::query::{
     AnalyzeQueryResponse, AnalyzeQueryResponseErrors, AstResponse, FluxSuggestion, FluxSuggestions,
-    LanguageRequest, Query,
+    LanguageRequest, Profiler, Query, QueryParam,
 };
 pub mod file;
 pub use self::file::File;
 pub mod health;
 pub use self::health::{HealthCheck, Status};
+pub mod config;
+pub use self::config::{Config, Flags};
+pub mod metrics;
+pub use self::metrics::{MetricFamily, MetricType, MetricValue, Metrics};
 pub mod data_point;
 pub use data_point::{DataPoint, FieldValue, WriteDataPoint};
 pub mod task;
 pub use task::{Task, TaskStatusType, Tasks};
+pub mod backup;
+pub use backup::{
+    BackupManifest, BucketMetadataManifest, FileCompression, ManifestFileEntry,
+    RetentionPolicyManifest, ShardGroupManifest, ShardManifest, ShardOwner, SubscriptionManifest,
+};
+pub mod annotation;
+pub use annotation::{AnnotationCreate, AnnotationEvent, AnnotationStream, StreamCreate};
+pub mod notebook;
+pub use notebook::{Notebook, NotebookParams, Notebooks};
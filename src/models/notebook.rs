@@ -0,0 +1,66 @@
+//! Notebooks
+
+use serde::{Deserialize, Serialize};
+
+/// Notebook Schema
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notebook {
+    /// Notebook ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Organization ID of notebook
+    #[serde(rename = "orgID", skip_serializing_if = "Option::is_none")]
+    pub org_id: Option<String>,
+    /// Notebook name
+    pub name: String,
+    /// Notebook spec, kept as raw JSON as its layout is owned by the UI
+    #[serde(default)]
+    pub spec: serde_json::Value,
+    /// Notebook created timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    /// Notebook updated timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+impl Notebook {
+    /// Returns the parameters needed to recreate this notebook in the
+    /// organization specified by `org_id`.
+    pub fn to_params(&self, org_id: String) -> NotebookParams {
+        NotebookParams {
+            org_id,
+            name: self.name.clone(),
+            spec: self.spec.clone(),
+        }
+    }
+}
+
+/// NotebookParams : Body of create and update notebook requests
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookParams {
+    /// Organization ID of notebook
+    #[serde(rename = "orgID")]
+    pub org_id: String,
+    /// Notebook name
+    pub name: String,
+    /// Notebook spec
+    pub spec: serde_json::Value,
+}
+
+impl NotebookParams {
+    /// Returns instance of NotebookParams
+    pub fn new(org_id: String, name: String, spec: serde_json::Value) -> Self {
+        Self { org_id, name, spec }
+    }
+}
+
+/// Notebooks
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct Notebooks {
+    /// List of notebooks
+    #[serde(default, rename = "flows")]
+    pub notebooks: Vec<Notebook>,
+}
@@ -1,10 +1,15 @@
 //! Query
 
 use crate::models::ast::dialect::Annotations;
-use crate::models::ast::Package;
+use crate::models::ast::{
+    Assignment, Expression, ImportDeclaration, MemberAssignment, MemberExpression, OptionStatement,
+    Package, Statement,
+};
 use crate::models::File;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use chrono::{DateTime, Offset, SecondsFormat, TimeZone};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
 
 /// Query influx using the Flux language
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -24,6 +29,9 @@ pub struct Query {
     /// Default is the server's now time.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub now: Option<String>,
+    /// Values bound to the query, available as `params.<name>` in Flux.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, QueryParam>,
 }
 
 impl Query {
@@ -34,6 +42,60 @@ impl Query {
             ..Default::default()
         }
     }
+
+    /// Bind a value to `params.<name>`.
+    ///
+    /// Bound values are sent next to the script instead of being pasted into
+    /// it, so they can never change the meaning of the query.
+    pub fn bind(mut self, name: impl Into<String>, value: impl Into<QueryParam>) -> Self {
+        self.params.insert(name.into(), value.into());
+        self
+    }
+
+    /// Enable Flux profilers, setting `profiler.enabledProfilers` through
+    /// `extern`. Their results are returned as extra tables next to the
+    /// data, see `Client::query_profiled`.
+    pub fn with_profilers(mut self, profilers: &[Profiler]) -> Self {
+        let mut file = self.r#extern.take().unwrap_or_else(|| File {
+            r#type: Some("File".to_owned()),
+            ..File::default()
+        });
+        if !file.imports.iter().any(|i| i.path.value == "profiler") {
+            file.imports.push(ImportDeclaration::new("profiler"));
+        }
+        file.body.push(Statement::Option(OptionStatement {
+            assignment: Assignment::Member(MemberAssignment::new(
+                MemberExpression::new(Expression::identifier("profiler"), "enabledProfilers"),
+                Expression::array(
+                    profilers
+                        .iter()
+                        .map(|p| Expression::string(p.name()))
+                        .collect(),
+                ),
+            )),
+        }));
+        self.r#extern = Some(file);
+        self
+    }
+}
+
+/// Flux profiler
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Profiler {
+    /// Profile of the whole query, the `profiler/query` table
+    Query,
+    /// Profile of each operator, the `profiler/operator` table
+    Operator,
+}
+
+impl Profiler {
+    /// Name of the profiler in Flux
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::Operator => "operator",
+        }
+    }
 }
 
 impl Default for Query {
@@ -51,10 +113,170 @@ impl Default for Query {
                 ..Default::default()
             }),
             now: None,
+            params: BTreeMap::new(),
         }
     }
 }
 
+/// Value bound to a query parameter.
+///
+/// Durations and times have no JSON representation, so they are sent as
+/// strings and have to be converted in Flux with `duration(v: params.x)` and
+/// `time(v: params.x)` respectively.
+#[derive(Clone, Debug, PartialEq)]
+pub enum QueryParam {
+    /// String value
+    String(String),
+    /// Integer value
+    Int(i64),
+    /// Unsigned integer value
+    UInt(u64),
+    /// Float value
+    Float(f64),
+    /// Boolean value
+    Bool(bool),
+    /// Duration value, sent as a Flux duration literal such as `1h30m`
+    Duration(chrono::Duration),
+    /// Time value, sent as an RFC3339 timestamp
+    Time(DateTime<chrono::FixedOffset>),
+}
+
+impl QueryParam {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            Self::String(v) => v.clone().into(),
+            Self::Int(v) => (*v).into(),
+            Self::UInt(v) => (*v).into(),
+            Self::Float(v) => (*v).into(),
+            Self::Bool(v) => (*v).into(),
+            Self::Duration(v) => format_duration(v).into(),
+            Self::Time(v) => v.to_rfc3339_opts(SecondsFormat::AutoSi, true).into(),
+        }
+    }
+}
+
+/// Formats a duration as a Flux duration literal
+pub(crate) fn format_duration(duration: &chrono::Duration) -> String {
+    let mut out = String::new();
+    let duration = if *duration < chrono::Duration::zero() {
+        out.push('-');
+        -*duration
+    } else {
+        *duration
+    };
+
+    let mut secs = duration.num_seconds();
+    let mut nanos = (duration - chrono::Duration::seconds(secs))
+        .num_nanoseconds()
+        .unwrap_or_default();
+    for (unit, size) in [("d", 86400), ("h", 3600), ("m", 60), ("s", 1)] {
+        if secs >= size {
+            write!(out, "{}{}", secs / size, unit).unwrap();
+            secs %= size;
+        }
+    }
+    for (unit, size) in [("ms", 1_000_000), ("us", 1_000), ("ns", 1)] {
+        if nanos >= size {
+            write!(out, "{}{}", nanos / size, unit).unwrap();
+            nanos %= size;
+        }
+    }
+    if out.is_empty() || out == "-" {
+        out = String::from("0s");
+    }
+    out
+}
+
+impl Serialize for QueryParam {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for QueryParam {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::String(v) => Ok(Self::String(v)),
+            serde_json::Value::Bool(v) => Ok(Self::Bool(v)),
+            serde_json::Value::Number(v) => {
+                if let Some(v) = v.as_i64() {
+                    Ok(Self::Int(v))
+                } else if let Some(v) = v.as_u64() {
+                    Ok(Self::UInt(v))
+                } else {
+                    Ok(Self::Float(v.as_f64().unwrap_or_default()))
+                }
+            }
+            other => Err(D::Error::custom(format!(
+                "unsupported query parameter: {}",
+                other
+            ))),
+        }
+    }
+}
+
+impl From<&str> for QueryParam {
+    fn from(v: &str) -> Self {
+        Self::String(v.to_owned())
+    }
+}
+
+impl From<String> for QueryParam {
+    fn from(v: String) -> Self {
+        Self::String(v)
+    }
+}
+
+impl From<i64> for QueryParam {
+    fn from(v: i64) -> Self {
+        Self::Int(v)
+    }
+}
+
+impl From<i32> for QueryParam {
+    fn from(v: i32) -> Self {
+        Self::Int(v.into())
+    }
+}
+
+impl From<u64> for QueryParam {
+    fn from(v: u64) -> Self {
+        Self::UInt(v)
+    }
+}
+
+impl From<u32> for QueryParam {
+    fn from(v: u32) -> Self {
+        Self::UInt(v.into())
+    }
+}
+
+impl From<f64> for QueryParam {
+    fn from(v: f64) -> Self {
+        Self::Float(v)
+    }
+}
+
+impl From<bool> for QueryParam {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+impl From<chrono::Duration> for QueryParam {
+    fn from(v: chrono::Duration) -> Self {
+        Self::Duration(v)
+    }
+}
+
+impl<Tz: TimeZone> From<DateTime<Tz>> for QueryParam {
+    fn from(v: DateTime<Tz>) -> Self {
+        Self::Time(v.with_timezone(&v.offset().fix()))
+    }
+}
+
 /// The type of query. Must be \"flux\".
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -163,3 +385,99 @@ impl LanguageRequest {
         Self { query }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn serialize_params() {
+        let time = Utc.ymd(2023, 1, 2).and_hms(3, 4, 5);
+        let query = Query::new("from(bucket: params.bucket)".to_owned())
+            .bind("bucket", r#"my"bucket"#)
+            .bind("limit", 10)
+            .bind("ratio", 0.5)
+            .bind("desc", true)
+            .bind("every", chrono::Duration::seconds(5400))
+            .bind("start", time);
+
+        let json = serde_json::to_value(&query).unwrap();
+
+        assert_eq!(
+            json["params"],
+            serde_json::json!({
+                "bucket": "my\"bucket",
+                "desc": true,
+                "every": "1h30m",
+                "limit": 10,
+                "ratio": 0.5,
+                "start": "2023-01-02T03:04:05Z",
+            })
+        );
+    }
+
+    #[test]
+    fn serialize_profilers() {
+        let query = Query::new("buckets()".to_owned())
+            .with_profilers(&[Profiler::Query, Profiler::Operator]);
+
+        let json = serde_json::to_value(&query).unwrap();
+
+        assert_eq!(
+            json["extern"],
+            serde_json::json!({
+                "type": "File",
+                "imports": [{"path": {"value": "profiler"}}],
+                "body": [{
+                    "type": "OptionStatement",
+                    "assignment": {
+                        "type": "MemberAssignment",
+                        "member": {
+                            "object": {"type": "Identifier", "name": "profiler"},
+                            "property": {"type": "Identifier", "name": "enabledProfilers"},
+                        },
+                        "init": {
+                            "type": "ArrayExpression",
+                            "elements": [
+                                {"type": "StringLiteral", "value": "query"},
+                                {"type": "StringLiteral", "value": "operator"},
+                            ],
+                        },
+                    },
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn import_profiler_once() {
+        let query = Query::new("buckets()".to_owned())
+            .with_profilers(&[Profiler::Query])
+            .with_profilers(&[Profiler::Operator]);
+
+        let json = serde_json::to_value(&query).unwrap();
+
+        assert_eq!(
+            json["extern"]["imports"],
+            serde_json::json!([{"path": {"value": "profiler"}}])
+        );
+    }
+
+    #[test]
+    fn skip_empty_params() {
+        let json = serde_json::to_value(Query::new("buckets()".to_owned())).unwrap();
+
+        assert!(json.get("params").is_none());
+    }
+
+    #[test]
+    fn format_durations() {
+        assert_eq!(format_duration(&chrono::Duration::zero()), "0s");
+        assert_eq!(format_duration(&chrono::Duration::minutes(-30)), "-30m");
+        assert_eq!(
+            format_duration(&chrono::Duration::nanoseconds(1_500_000_001)),
+            "1s500ms1ns"
+        );
+    }
+}
@@ -88,3 +88,47 @@ pub enum Type {
     /// Views
     Views,
 }
+
+impl Type {
+    /// Name of the resource type as used in API paths, e.g. `buckets` in
+    /// `/api/v2/buckets/{bucketID}/labels`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Annotations => "annotations",
+            Self::Authorizations => "authorizations",
+            Self::Buckets => "buckets",
+            Self::Checks => "checks",
+            Self::Dashboards => "dashboards",
+            Self::Dbrp => "dbrp",
+            Self::Documents => "documents",
+            Self::Labels => "labels",
+            Self::Notebooks => "notebooks",
+            Self::NotificationEndpoints => "notificationEndpoints",
+            Self::NotificationRules => "notificationRules",
+            Self::Orgs => "orgs",
+            Self::Remotes => "remotes",
+            Self::Replications => "replications",
+            Self::Scrapers => "scrapers",
+            Self::Secrets => "secrets",
+            Self::Sources => "sources",
+            Self::Tasks => "tasks",
+            Self::Telegrafs => "telegrafs",
+            Self::Users => "users",
+            Self::Variables => "variables",
+            Self::Views => "views",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_as_str_matches_serde_name() {
+        for t in [Type::Buckets, Type::NotificationEndpoints, Type::Dbrp] {
+            let v = serde_json::to_string(&t).unwrap();
+            assert_eq!(v, format!("\"{}\"", t.as_str()));
+        }
+    }
+}
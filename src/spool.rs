@@ -0,0 +1,547 @@
+//! Spool
+//!
+//! Durable on-disk buffer for writes that could not be delivered. Failed
+//! batches are appended to segment files and replayed in order once the
+//! server reports healthy again, see `ClientBuilder::spool` and
+//! `Client::replay_spool`.
+
+use std::collections::VecDeque;
+use std::convert::TryInto;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use parking_lot::Mutex;
+
+use crate::api::write::TimestampPrecision;
+
+const SEGMENT_EXTENSION: &str = "seg";
+
+/// Configuration of the write spool
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpoolOptions {
+    /// Directory holding the segment files
+    pub dir: PathBuf,
+    /// Maximum size of all segments on disk, in bytes. Oldest segments are
+    /// dropped first when it is exceeded.
+    pub max_size: u64,
+    /// Size in bytes after which a new segment is started
+    pub segment_size: u64,
+}
+
+impl SpoolOptions {
+    /// Spool in `dir`, capped at 1 GiB in segments of 16 MiB
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_size: 1 << 30,
+            segment_size: 16 << 20,
+        }
+    }
+
+    /// Set the maximum size of the spool on disk
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = bytes;
+        self
+    }
+
+    /// Set the size after which a new segment is started
+    pub fn segment_size(mut self, bytes: u64) -> Self {
+        self.segment_size = bytes;
+        self
+    }
+}
+
+/// Outcome of `Client::replay_spool`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// Number of batches written
+    pub written: usize,
+    /// Number of batches rejected by the server as invalid, and dropped
+    pub dropped: usize,
+}
+
+/// Line protocol batch destined to a bucket
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Batch {
+    pub(crate) org: String,
+    pub(crate) bucket: String,
+    pub(crate) precision: TimestampPrecision,
+    pub(crate) body: Bytes,
+}
+
+impl Batch {
+    /// Encodes the batch as a record: the length and the CRC-32 of the
+    /// payload, then the payload. Fails when the payload is too large for
+    /// its length to fit the record.
+    fn encode(&self) -> io::Result<Vec<u8>> {
+        let mut payload =
+            Vec::with_capacity(self.org.len() + self.bucket.len() + self.body.len() + 17);
+        for field in [self.org.as_bytes(), self.bucket.as_bytes()] {
+            payload.extend_from_slice(&record_len(field.len())?.to_be_bytes());
+            payload.extend_from_slice(field);
+        }
+        payload.push(match self.precision {
+            TimestampPrecision::Seconds => 0,
+            TimestampPrecision::Milliseconds => 1,
+            TimestampPrecision::Microseconds => 2,
+            TimestampPrecision::Nanoseconds => 3,
+        });
+        payload.extend_from_slice(&(self.body.len() as u64).to_be_bytes());
+        payload.extend_from_slice(&self.body);
+
+        let mut record = Vec::with_capacity(payload.len() + 8);
+        record.extend_from_slice(&record_len(payload.len())?.to_be_bytes());
+        record.extend_from_slice(&crc32fast::hash(&payload).to_be_bytes());
+        record.extend_from_slice(&payload);
+        Ok(record)
+    }
+
+    /// Decodes the batches of a segment. The first record that is cut short
+    /// or fails its checksum, e.g. after a crash while appending, ends the
+    /// segment.
+    fn decode_all(mut data: &[u8]) -> Vec<Self> {
+        let mut batches = vec![];
+        while let Some((batch, rest)) = Self::decode_record(data) {
+            batches.push(batch);
+            data = rest;
+        }
+        batches
+    }
+
+    fn decode_record(data: &[u8]) -> Option<(Self, &[u8])> {
+        let (len, data) = split(data, 4)?;
+        let len = u32::from_be_bytes(len.try_into().ok()?) as usize;
+        let (crc, data) = split(data, 4)?;
+        let crc = u32::from_be_bytes(crc.try_into().ok()?);
+        let (payload, data) = split(data, len)?;
+        if crc32fast::hash(payload) != crc {
+            return None;
+        }
+        Some((Self::decode(payload)?, data))
+    }
+
+    fn decode(data: &[u8]) -> Option<Self> {
+        fn string(data: &[u8]) -> Option<(String, &[u8])> {
+            let (len, data) = split(data, 4)?;
+            let len = u32::from_be_bytes(len.try_into().ok()?) as usize;
+            let (value, data) = split(data, len)?;
+            Some((String::from_utf8(value.to_vec()).ok()?, data))
+        }
+
+        let (org, data) = string(data)?;
+        let (bucket, data) = string(data)?;
+        let (precision, data) = split(data, 1)?;
+        let precision = match precision[0] {
+            0 => TimestampPrecision::Seconds,
+            1 => TimestampPrecision::Milliseconds,
+            2 => TimestampPrecision::Microseconds,
+            _ => TimestampPrecision::Nanoseconds,
+        };
+        let (len, data) = split(data, 8)?;
+        let len = u64::from_be_bytes(len.try_into().ok()?) as usize;
+        let (body, _) = split(data, len)?;
+        Some(Self {
+            org,
+            bucket,
+            precision,
+            body: Bytes::copy_from_slice(body),
+        })
+    }
+}
+
+fn record_len(len: usize) -> io::Result<u32> {
+    len.try_into().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} bytes do not fit in a spool record", len),
+        )
+    })
+}
+
+fn split(data: &[u8], len: usize) -> Option<(&[u8], &[u8])> {
+    (data.len() >= len).then(|| data.split_at(len))
+}
+
+#[derive(Debug)]
+struct Segment {
+    id: u64,
+    size: u64,
+}
+
+#[derive(Debug)]
+struct State {
+    segments: VecDeque<Segment>,
+    /// Segment new batches are appended to
+    active: Option<u64>,
+    next_id: u64,
+}
+
+/// Segment files in a directory, oldest first
+///
+/// The disk is only touched from blocking tasks, one at a time under
+/// `disk`. `state` is only held for in-memory bookkeeping, so reading the
+/// size of the spool never waits on the disk.
+#[derive(Debug)]
+pub(crate) struct Spool {
+    options: SpoolOptions,
+    state: Mutex<State>,
+    disk: Mutex<()>,
+    replaying: AtomicBool,
+}
+
+impl Spool {
+    /// Opens the spool, picking up the segments left by a previous run
+    pub(crate) fn open(options: SpoolOptions) -> io::Result<Self> {
+        fs::create_dir_all(&options.dir)?;
+        let mut segments = vec![];
+        for entry in fs::read_dir(&options.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(SEGMENT_EXTENSION) {
+                continue;
+            }
+            let id = path.file_stem().and_then(|s| s.to_str()?.parse().ok());
+            if let Some(id) = id {
+                segments.push(Segment {
+                    id,
+                    size: fs::metadata(&path)?.len(),
+                });
+            }
+        }
+        segments.sort_by_key(|s| s.id);
+        let next_id = segments.last().map_or(0, |s| s.id + 1);
+
+        Ok(Self {
+            options,
+            state: Mutex::new(State {
+                segments: segments.into(),
+                active: None,
+                next_id,
+            }),
+            disk: Mutex::new(()),
+            replaying: AtomicBool::new(false),
+        })
+    }
+
+    fn path(&self, id: u64) -> PathBuf {
+        segment_path(&self.options.dir, id)
+    }
+
+    /// Whether no batch is waiting to be replayed
+    pub(crate) fn is_empty(&self) -> bool {
+        self.state.lock().segments.is_empty()
+    }
+
+    /// Size of the segments on disk, in bytes
+    pub(crate) fn size(&self) -> u64 {
+        self.state.lock().segments.iter().map(|s| s.size).sum()
+    }
+
+    /// Size in bytes after which a new segment is started
+    pub(crate) fn segment_size(&self) -> u64 {
+        self.options.segment_size
+    }
+
+    /// Durably appends a batch, then drops the oldest segments while the
+    /// spool is over its size cap
+    pub(crate) async fn append(self: &Arc<Self>, batch: &Batch) -> io::Result<()> {
+        let record = batch.encode()?;
+        self.on_disk(move |spool| spool.append_record(&record))
+            .await
+    }
+
+    /// Reads the oldest segment, which no longer receives appends afterwards
+    pub(crate) async fn oldest(self: &Arc<Self>) -> io::Result<Option<(u64, Vec<Batch>)>> {
+        self.on_disk(Self::read_oldest).await
+    }
+
+    /// Replaces the content of a segment read by `oldest` with the batches
+    /// that are still pending, removing it when there are none. Segments
+    /// dropped in the meantime are left alone.
+    pub(crate) async fn retain(self: &Arc<Self>, id: u64, pending: Vec<Batch>) -> io::Result<()> {
+        self.on_disk(move |spool| spool.rewrite(id, &pending)).await
+    }
+
+    /// Runs `f` on the blocking thread pool, holding the disk lock
+    async fn on_disk<T, F>(self: &Arc<Self>, f: F) -> io::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Self) -> io::Result<T> + Send + 'static,
+    {
+        let spool = Arc::clone(self);
+        tokio::task::spawn_blocking(move || {
+            let _disk = spool.disk.lock();
+            f(&spool)
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+
+    fn append_record(&self, record: &[u8]) -> io::Result<()> {
+        let (id, created) = {
+            let mut state = self.state.lock();
+            let active = state.active.filter(|id| {
+                state
+                    .segments
+                    .back()
+                    .is_some_and(|s| s.id == *id && s.size < self.options.segment_size)
+            });
+            match active {
+                Some(id) => (id, false),
+                None => {
+                    let id = state.next_id;
+                    state.next_id += 1;
+                    state.segments.push_back(Segment { id, size: 0 });
+                    state.active = Some(id);
+                    (id, true)
+                }
+            }
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(id))?;
+        file.write_all(record)?;
+        file.sync_data()?;
+        if created {
+            sync_dir(&self.options.dir)?;
+        }
+
+        let dropped: Vec<u64> = {
+            let mut state = self.state.lock();
+            if let Some(segment) = state.segments.iter_mut().find(|s| s.id == id) {
+                segment.size += record.len() as u64;
+            }
+
+            let mut dropped = vec![];
+            let mut size: u64 = state.segments.iter().map(|s| s.size).sum();
+            while size > self.options.max_size {
+                let segment = match state.segments.pop_front() {
+                    Some(segment) => segment,
+                    None => break,
+                };
+                if state.active == Some(segment.id) {
+                    state.active = None;
+                }
+                size -= segment.size;
+                dropped.push(segment.id);
+            }
+            dropped
+        };
+        for id in dropped {
+            remove_file(&self.path(id))?;
+        }
+        Ok(())
+    }
+
+    fn read_oldest(&self) -> io::Result<Option<(u64, Vec<Batch>)>> {
+        let id = {
+            let mut state = self.state.lock();
+            let id = match state.segments.front() {
+                Some(segment) => segment.id,
+                None => return Ok(None),
+            };
+            if state.active == Some(id) {
+                state.active = None;
+            }
+            id
+        };
+
+        let mut data = vec![];
+        match File::open(self.path(id)) {
+            Ok(mut file) => {
+                file.read_to_end(&mut data)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        Ok(Some((id, Batch::decode_all(&data))))
+    }
+
+    fn rewrite(&self, id: u64, pending: &[Batch]) -> io::Result<()> {
+        let path = self.path(id);
+        if !self.state.lock().segments.iter().any(|s| s.id == id) {
+            return Ok(());
+        }
+
+        if pending.is_empty() {
+            self.state.lock().segments.retain(|s| s.id != id);
+            remove_file(&path)?;
+            return sync_dir(&self.options.dir);
+        }
+
+        let mut data = vec![];
+        for batch in pending {
+            data.extend(batch.encode()?);
+        }
+        let tmp = path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&data)?;
+        file.sync_data()?;
+        fs::rename(&tmp, &path)?;
+        sync_dir(&self.options.dir)?;
+        if let Some(segment) = self.state.lock().segments.iter_mut().find(|s| s.id == id) {
+            segment.size = data.len() as u64;
+        }
+        Ok(())
+    }
+
+    /// Marks the spool as being replayed, returns `None` when a replay is
+    /// already running
+    pub(crate) fn start_replay(&self) -> Option<ReplayGuard<'_>> {
+        self.replaying
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| ReplayGuard(&self.replaying))
+    }
+}
+
+/// Ends the replay when dropped
+#[derive(Debug)]
+pub(crate) struct ReplayGuard<'a>(&'a AtomicBool);
+
+impl Drop for ReplayGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+fn segment_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", id, SEGMENT_EXTENSION))
+}
+
+/// Makes the creation, renaming and removal of the segments in `dir`
+/// durable
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+fn remove_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(body: &'static str) -> Batch {
+        Batch {
+            org: "org".to_owned(),
+            bucket: "bucket".to_owned(),
+            precision: TimestampPrecision::Seconds,
+            body: Bytes::from_static(body.as_bytes()),
+        }
+    }
+
+    fn open(options: SpoolOptions) -> Arc<Spool> {
+        Arc::new(Spool::open(options).unwrap())
+    }
+
+    #[tokio::test]
+    async fn append_and_reopen() {
+        let dir = test_helpers::tmp_dir().unwrap();
+        let options = SpoolOptions::new(dir.path()).segment_size(1);
+
+        let spool = open(options.clone());
+        spool.append(&batch("cpu usage=1 1\n")).await.unwrap();
+        spool.append(&batch("cpu usage=2 2\n")).await.unwrap();
+        assert_eq!(spool.state.lock().segments.len(), 2);
+
+        // Simulate a crash in the middle of an append
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(segment_path(dir.path(), 1))
+            .unwrap();
+        file.write_all(&batch("cpu usage=3 3\n").encode().unwrap()[..10])
+            .unwrap();
+
+        let spool = open(options);
+        let (id, batches) = spool.oldest().await.unwrap().unwrap();
+        assert_eq!(batches, vec![batch("cpu usage=1 1\n")]);
+        spool.retain(id, vec![]).await.unwrap();
+
+        let (id, batches) = spool.oldest().await.unwrap().unwrap();
+        assert_eq!(batches, vec![batch("cpu usage=2 2\n")]);
+        spool.retain(id, vec![]).await.unwrap();
+
+        assert!(spool.oldest().await.unwrap().is_none());
+        assert!(spool.is_empty());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn stops_at_corrupted_record() {
+        let dir = test_helpers::tmp_dir().unwrap();
+        let options = SpoolOptions::new(dir.path());
+
+        let spool = open(options.clone());
+        spool.append(&batch("cpu usage=1 1\n")).await.unwrap();
+        spool.append(&batch("cpu usage=2 2\n")).await.unwrap();
+        spool.append(&batch("cpu usage=3 3\n")).await.unwrap();
+
+        // Flip a byte in the body of the second record
+        let path = segment_path(dir.path(), 0);
+        let mut data = fs::read(&path).unwrap();
+        let record_size = batch("cpu usage=1 1\n").encode().unwrap().len();
+        data[record_size * 2 - 3] ^= 0xff;
+        fs::write(&path, data).unwrap();
+
+        let spool = open(options);
+        let (_, batches) = spool.oldest().await.unwrap().unwrap();
+        assert_eq!(batches, vec![batch("cpu usage=1 1\n")]);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn rejects_oversized_records() {
+        assert_eq!(record_len(u32::MAX as usize).unwrap(), u32::MAX);
+        assert_eq!(
+            record_len(u32::MAX as usize + 1).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[tokio::test]
+    async fn drops_oldest_over_cap() {
+        let dir = test_helpers::tmp_dir().unwrap();
+        let record_size = batch("cpu usage=1 1\n").encode().unwrap().len() as u64;
+        let spool = open(
+            SpoolOptions::new(dir.path())
+                .segment_size(record_size)
+                .max_size(record_size * 2),
+        );
+
+        spool.append(&batch("cpu usage=1 1\n")).await.unwrap();
+        spool.append(&batch("cpu usage=2 2\n")).await.unwrap();
+        spool.append(&batch("cpu usage=3 3\n")).await.unwrap();
+
+        assert_eq!(spool.size(), record_size * 2);
+        let (_, batches) = spool.oldest().await.unwrap().unwrap();
+        assert_eq!(batches, vec![batch("cpu usage=2 2\n")]);
+    }
+
+    #[tokio::test]
+    async fn sealed_after_read() {
+        let dir = test_helpers::tmp_dir().unwrap();
+        let spool = open(SpoolOptions::new(dir.path()));
+
+        spool.append(&batch("cpu usage=1 1\n")).await.unwrap();
+        let (id, batches) = spool.oldest().await.unwrap().unwrap();
+        spool.append(&batch("cpu usage=2 2\n")).await.unwrap();
+        spool.retain(id, batches[1..].to_vec()).await.unwrap();
+
+        let (_, batches) = spool.oldest().await.unwrap().unwrap();
+        assert_eq!(batches, vec![batch("cpu usage=2 2\n")]);
+    }
+}
@@ -2,9 +2,47 @@
 //!
 //! Trying to construct the trait used for line protocol
 //! https://docs.influxdata.com/influxdb/v2.6/reference/syntax/line-protocol/#Copyright
-
+//!
+//! The traits write straight into an `io::Write`, e.g. a `Vec<u8>` or a
+//! `BufMut` through `BufMut::writer`. The `encode_*` methods return a
+//! `String` instead, for convenience, and panic on values line protocol
+//! cannot represent, like a key ending with a backslash.
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::hash::BuildHasher;
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::models::data_point::{write_key, write_measurement, write_string_field};
+use crate::models::WriteDataPoint;
 use influxdb2_derive::{impl_tuple_fields, impl_tuple_tags};
 
+/// Encode a measurement name, escaping commas and spaces
+pub fn encode_measurement(measurement: &str) -> String {
+    encode(|w| write_measurement_to(measurement, w))
+}
+
+/// Write a measurement name, escaping commas and spaces
+pub fn write_measurement_to<W: Write>(measurement: &str, w: W) -> io::Result<()> {
+    write_measurement(measurement, w)
+}
+
+fn encode(write: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> String {
+    let mut buf = vec![];
+    if let Err(e) = write(&mut buf) {
+        panic!("cannot encode as line protocol: {}", e);
+    }
+    String::from_utf8(buf).expect("line protocol of UTF-8 values is valid UTF-8")
+}
+
+/// Write a float the way `DataPoint` does, without the `.0` of integers
+fn write_f64<W: Write>(value: f64, mut w: W) -> io::Result<()> {
+    let mut buffer = ryu::Buffer::new();
+    let formatted = buffer.format(value);
+    w.write_all(formatted.strip_suffix(".0").unwrap_or(formatted).as_bytes())
+}
+
 /// InfluxDB WritableValue trait
 ///
 /// This type normally descript the type which could be written as FieldValue.
@@ -12,74 +50,82 @@ use influxdb2_derive::{impl_tuple_fields, impl_tuple_tags};
 /// The influxdb support value type is
 /// Value data type: Float | Integer | UInteger | String | Boolean
 pub trait ValueWritable {
-    /// encode_value into influxdb support string
-    fn encode_value(&self) -> String;
-}
-
-struct Value<T: ValueWritable> {
-    inner: T,
-}
+    /// write the value as line protocol
+    fn write_value_to<W: Write>(&self, w: W) -> io::Result<()>;
 
-impl<T: ValueWritable> From<T> for Value<T> {
-    fn from(value: T) -> Self {
-        Self { inner: value }
-    }
-}
-impl<T: ValueWritable> ValueWritable for Value<T> {
+    /// encode_value into influxdb support string
     fn encode_value(&self) -> String {
-        self.inner.encode_value()
+        encode(|w| self.write_value_to(w))
+    }
+
+    /// Whether there is a value to write, `None` fields are left out of
+    /// the line
+    fn is_present(&self) -> bool {
+        true
     }
 }
 
 impl ValueWritable for f64 {
-    fn encode_value(&self) -> String {
-        self.to_string()
+    fn write_value_to<W: Write>(&self, w: W) -> io::Result<()> {
+        write_f64(*self, w)
     }
 }
 
 impl ValueWritable for i64 {
-    fn encode_value(&self) -> String {
-        format!("{self}i")
+    fn write_value_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        itoa::write(&mut w, *self)?;
+        w.write_all(b"i")
     }
 }
 
 impl ValueWritable for u64 {
-    fn encode_value(&self) -> String {
-        format!("{self}u")
+    fn write_value_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        itoa::write(&mut w, *self)?;
+        w.write_all(b"u")
     }
 }
 
 impl ValueWritable for String {
-    fn encode_value(&self) -> String {
-        format!("\"{}\"", self)
+    fn write_value_to<W: Write>(&self, w: W) -> io::Result<()> {
+        write_string_field(self, w)
     }
 }
 
 impl ValueWritable for &str {
-    fn encode_value(&self) -> String {
-        format!("\"{}\"", self)
+    fn write_value_to<W: Write>(&self, w: W) -> io::Result<()> {
+        write_string_field(self, w)
     }
 }
 
 impl ValueWritable for bool {
-    fn encode_value(&self) -> String {
+    fn write_value_to<W: Write>(&self, mut w: W) -> io::Result<()> {
         // bool type in influxdb2
         // https://docs.influxdata.com/influxdb/v2.6/reference/syntax/line-protocol/#boolean
-        if *self {
-            "t".to_string()
-        } else {
-            "f".to_string()
-        }
+        w.write_all(if *self { b"t" } else { b"f" })
+    }
+}
+
+impl<T: ValueWritable + ?Sized> ValueWritable for &T {
+    fn write_value_to<W: Write>(&self, w: W) -> io::Result<()> {
+        (**self).write_value_to(w)
+    }
+
+    fn is_present(&self) -> bool {
+        (**self).is_present()
     }
 }
 
 impl<T: ValueWritable> ValueWritable for Option<T> {
-    fn encode_value(&self) -> String {
+    fn write_value_to<W: Write>(&self, mut w: W) -> io::Result<()> {
         match self {
-            Some(v) => v.encode_value(),
-            None => "\"None\"".to_string(),
+            Some(v) => v.write_value_to(w),
+            None => w.write_all(b"\"None\""),
         }
     }
+
+    fn is_present(&self) -> bool {
+        self.is_some()
+    }
 }
 
 /// InfluxDB Key
@@ -90,67 +136,144 @@ impl<T: ValueWritable> ValueWritable for Option<T> {
 /// Key data type: String
 /// Value data type: String
 pub trait KeyWritable {
-    /// encode key as string
-    fn encode_key(&self) -> String;
-}
+    /// write the key as line protocol, escaping commas, equal signs and
+    /// spaces. Fails on a key ending with a backslash, which would escape
+    /// the delimiter after it.
+    fn write_key_to<W: Write>(&self, w: W) -> io::Result<()>;
 
-struct Key<T: KeyWritable> {
-    inner: T,
-}
-
-impl<T: KeyWritable> From<T> for Key<T> {
-    fn from(value: T) -> Self {
-        Self { inner: value }
+    /// encode key as string, escaping commas, equal signs and spaces
+    fn encode_key(&self) -> String {
+        encode(|w| self.write_key_to(w))
     }
-}
 
-impl<T: KeyWritable> KeyWritable for Key<T> {
-    fn encode_key(&self) -> String {
-        self.inner.encode_key()
+    /// Whether there is a value to write, `None` and empty tags are left out
+    /// of the line as line protocol has no empty tag values
+    fn is_present(&self) -> bool {
+        true
     }
 }
 
 impl KeyWritable for &str {
-    fn encode_key(&self) -> String {
-        self.to_string()
+    fn write_key_to<W: Write>(&self, w: W) -> io::Result<()> {
+        write_key(self, w)
+    }
+
+    fn is_present(&self) -> bool {
+        !self.is_empty()
     }
 }
 
 impl KeyWritable for String {
-    fn encode_key(&self) -> String {
-        self.clone()
+    fn write_key_to<W: Write>(&self, w: W) -> io::Result<()> {
+        write_key(self, w)
+    }
+
+    fn is_present(&self) -> bool {
+        !self.is_empty()
     }
 }
 
 impl KeyWritable for u64 {
-    fn encode_key(&self) -> String {
-        self.to_string()
+    fn write_key_to<W: Write>(&self, w: W) -> io::Result<()> {
+        itoa::write(w, *self).map(drop)
+    }
+}
+
+impl<T: KeyWritable + ?Sized> KeyWritable for &T {
+    fn write_key_to<W: Write>(&self, w: W) -> io::Result<()> {
+        (**self).write_key_to(w)
+    }
+
+    fn is_present(&self) -> bool {
+        (**self).is_present()
     }
 }
 
 impl<T: KeyWritable> KeyWritable for Option<T> {
-    fn encode_key(&self) -> String {
+    fn write_key_to<W: Write>(&self, mut w: W) -> io::Result<()> {
         match self {
-            Some(v) => v.encode_key(),
-            None => "None".to_string(),
+            Some(v) => v.write_key_to(w),
+            None => w.write_all(b"None"),
         }
     }
+
+    fn is_present(&self) -> bool {
+        self.as_ref().is_some_and(KeyWritable::is_present)
+    }
 }
 
 /// Write tags as key=value
 pub trait TagsWritable {
+    /// write the tags as line protocol
+    fn write_tags_to<W: Write>(&self, w: W) -> io::Result<()>;
+
     /// encode tags into string
-    fn encode_tags(&self) -> String;
+    fn encode_tags(&self) -> String {
+        encode(|w| self.write_tags_to(w))
+    }
 }
 
 impl_tuple_tags!((T1, T2));
 impl_tuple_tags!((T1, T2, T3, T4));
 impl_tuple_tags!((T1, T2, T3, T4, T5, T6));
 
+impl<K: KeyWritable, V: KeyWritable> TagsWritable for [(K, V)] {
+    fn write_tags_to<W: Write>(&self, w: W) -> io::Result<()> {
+        write_tags(self.iter().map(|(k, v)| (k, v)), w)
+    }
+}
+
+impl<K: KeyWritable, V: KeyWritable> TagsWritable for Vec<(K, V)> {
+    fn write_tags_to<W: Write>(&self, w: W) -> io::Result<()> {
+        self.as_slice().write_tags_to(w)
+    }
+}
+
+impl<K: KeyWritable, V: KeyWritable> TagsWritable for BTreeMap<K, V> {
+    fn write_tags_to<W: Write>(&self, w: W) -> io::Result<()> {
+        write_tags(self, w)
+    }
+}
+
+/// The tags are written in the iteration order of the map, sorting them by
+/// key, e.g. with a `BTreeMap`, makes the writes cheaper for the server
+impl<K: KeyWritable, V: KeyWritable, S: BuildHasher> TagsWritable for HashMap<K, V, S> {
+    fn write_tags_to<W: Write>(&self, w: W) -> io::Result<()> {
+        write_tags(self, w)
+    }
+}
+
+/// Write key=value pairs separated by commas, leaving out `None` values
+fn write_tags<K, V, W>(tags: impl IntoIterator<Item = (K, V)>, mut w: W) -> io::Result<()>
+where
+    K: KeyWritable,
+    V: KeyWritable,
+    W: Write,
+{
+    let mut first = true;
+    for (key, value) in tags {
+        if value.is_present() {
+            if !first {
+                w.write_all(b",")?;
+            }
+            first = false;
+            key.write_key_to(&mut w)?;
+            w.write_all(b"=")?;
+            value.write_key_to(&mut w)?;
+        }
+    }
+    Ok(())
+}
+
 /// Write tags as field=value
 pub trait FieldsWritable {
+    /// write the fields as line protocol
+    fn write_fields_to<W: Write>(&self, w: W) -> io::Result<()>;
+
     /// encode fields into string
-    fn encode_fields(&self) -> String;
+    fn encode_fields(&self) -> String {
+        encode(|w| self.write_fields_to(w))
+    }
 }
 
 impl_tuple_fields!((K1, V1));
@@ -161,34 +284,460 @@ impl_tuple_fields!((K1, V1, K2, V2, K3, V3, K4, V4, K5, V5));
 impl_tuple_fields!((K1, V1, K2, V2, K3, V3, K4, V4, K5, V5, K6, V6));
 impl_tuple_fields!((K1, V1, K2, V2, K3, V3, K4, V4, K5, V5, K6, V6, K7, V7));
 
+impl<K: KeyWritable, V: ValueWritable> FieldsWritable for [(K, V)] {
+    fn write_fields_to<W: Write>(&self, w: W) -> io::Result<()> {
+        write_fields(self.iter().map(|(k, v)| (k, v)), w)
+    }
+}
+
+impl<K: KeyWritable, V: ValueWritable> FieldsWritable for Vec<(K, V)> {
+    fn write_fields_to<W: Write>(&self, w: W) -> io::Result<()> {
+        self.as_slice().write_fields_to(w)
+    }
+}
+
+impl<K: KeyWritable, V: ValueWritable> FieldsWritable for BTreeMap<K, V> {
+    fn write_fields_to<W: Write>(&self, w: W) -> io::Result<()> {
+        write_fields(self, w)
+    }
+}
+
+impl<K: KeyWritable, V: ValueWritable, S: BuildHasher> FieldsWritable for HashMap<K, V, S> {
+    fn write_fields_to<W: Write>(&self, w: W) -> io::Result<()> {
+        write_fields(self, w)
+    }
+}
+
+/// Write key=value pairs separated by commas, leaving out `None` values
+fn write_fields<K, V, W>(fields: impl IntoIterator<Item = (K, V)>, mut w: W) -> io::Result<()>
+where
+    K: KeyWritable,
+    V: ValueWritable,
+    W: Write,
+{
+    let mut first = true;
+    for (key, value) in fields {
+        if value.is_present() {
+            if !first {
+                w.write_all(b",")?;
+            }
+            first = false;
+            key.write_key_to(&mut w)?;
+            w.write_all(b"=")?;
+            value.write_value_to(&mut w)?;
+        }
+    }
+    Ok(())
+}
+
 /// Any type wants to be a timestamp needs to implement this
 pub trait TimestampWritable {
+    /// write the timestamp as line protocol
+    fn write_timestamp_to<W: Write>(&self, w: W) -> io::Result<()>;
+
     /// encode into string like "1465839830100400200"
-    fn encode_timestamp(&self) -> String;
+    fn encode_timestamp(&self) -> String {
+        encode(|w| self.write_timestamp_to(w))
+    }
+
+    /// Whether there is a timestamp to write, the server time is used for
+    /// `None` timestamps
+    fn is_present(&self) -> bool {
+        true
+    }
 }
 
 impl TimestampWritable for u64 {
-    fn encode_timestamp(&self) -> String {
-        self.to_string()
+    fn write_timestamp_to<W: Write>(&self, w: W) -> io::Result<()> {
+        itoa::write(w, *self).map(drop)
     }
 }
 
 impl TimestampWritable for i64 {
-    fn encode_timestamp(&self) -> String {
-        self.to_string()
+    fn write_timestamp_to<W: Write>(&self, w: W) -> io::Result<()> {
+        itoa::write(w, *self).map(drop)
+    }
+}
+
+/// Encoded as nanoseconds, for writes with the default precision
+impl<Tz: chrono::TimeZone> TimestampWritable for chrono::DateTime<Tz> {
+    fn write_timestamp_to<W: Write>(&self, w: W) -> io::Result<()> {
+        self.timestamp_nanos().write_timestamp_to(w)
+    }
+}
+
+/// Encoded as nanoseconds, for writes with the default precision
+impl TimestampWritable for SystemTime {
+    fn write_timestamp_to<W: Write>(&self, w: W) -> io::Result<()> {
+        let nanos = match self.duration_since(UNIX_EPOCH) {
+            Ok(after) => i64::try_from(after.as_nanos()).ok(),
+            Err(before) => i64::try_from(before.duration().as_nanos())
+                .ok()
+                .map(|nanos| -nanos),
+        };
+        match nanos {
+            Some(nanos) => nanos.write_timestamp_to(w),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "timestamp out of the range of line protocol",
+            )),
+        }
+    }
+}
+
+impl<T: TimestampWritable> TimestampWritable for Option<T> {
+    fn write_timestamp_to<W: Write>(&self, w: W) -> io::Result<()> {
+        match self {
+            Some(v) => v.write_timestamp_to(w),
+            None => Ok(()),
+        }
+    }
+
+    fn is_present(&self) -> bool {
+        self.is_some()
+    }
+}
+
+/// Point whose tags and fields are only known at runtime
+///
+/// The tags and the fields are iterators of key-value pairs, e.g. a
+/// reference to a map, a `Vec` or a slice of pairs, iterated each time the
+/// point is written.
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use influxdb2::models::WriteDataPoint;
+/// use influxdb2::writable::DynamicPoint;
+///
+/// let tags: BTreeMap<String, String> = [("host".to_owned(), "server01".to_owned())].into();
+/// let fields = vec![("usage", 0.5), ("load", 1.25)];
+/// let point = DynamicPoint::new("cpu", &tags, &fields).timestamp(1_000_000_000);
+///
+/// let mut buf = Vec::new();
+/// point.write_data_point_to(&mut buf).unwrap();
+/// assert_eq!(&buf[..], b"cpu,host=server01 usage=0.5,load=1.25 1000000000\n");
+/// ```
+#[derive(Clone, Debug)]
+pub struct DynamicPoint<M, T, F> {
+    measurement: M,
+    tags: T,
+    fields: F,
+    timestamp: Option<i64>,
+}
+
+impl<M, T, F> DynamicPoint<M, T, F> {
+    /// Create a point without a timestamp, the server sets it on write
+    pub fn new(measurement: M, tags: T, fields: F) -> Self {
+        Self {
+            measurement,
+            tags,
+            fields,
+            timestamp: None,
+        }
+    }
+
+    /// Set the timestamp of the point
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+}
+
+/// Tag or field of a `DynamicPoint`, a key-value pair or a reference to one
+pub trait KeyValue {
+    /// Type of the key
+    type Key: ?Sized;
+    /// Type of the value
+    type Value: ?Sized;
+
+    /// The key and the value
+    fn key_value(&self) -> (&Self::Key, &Self::Value);
+}
+
+impl<K, V> KeyValue for (K, V) {
+    type Key = K;
+    type Value = V;
+
+    fn key_value(&self) -> (&K, &V) {
+        (&self.0, &self.1)
+    }
+}
+
+impl<K, V> KeyValue for &(K, V) {
+    type Key = K;
+    type Value = V;
+
+    fn key_value(&self) -> (&K, &V) {
+        (&self.0, &self.1)
+    }
+}
+
+impl<M, T, F> WriteDataPoint for DynamicPoint<M, T, F>
+where
+    M: AsRef<str>,
+    T: IntoIterator + Clone,
+    T::Item: KeyValue,
+    <T::Item as KeyValue>::Key: KeyWritable,
+    <T::Item as KeyValue>::Value: KeyWritable,
+    F: IntoIterator + Clone,
+    F::Item: KeyValue,
+    <F::Item as KeyValue>::Key: KeyWritable,
+    <F::Item as KeyValue>::Value: ValueWritable,
+{
+    fn write_data_point_to<W>(&self, mut w: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        write_measurement_to(self.measurement.as_ref(), &mut w)?;
+        for tag in self.tags.clone() {
+            let (key, value) = tag.key_value();
+            if value.is_present() {
+                w.write_all(b",")?;
+                key.write_key_to(&mut w)?;
+                w.write_all(b"=")?;
+                value.write_key_to(&mut w)?;
+            }
+        }
+        let mut fields = 0;
+        for field in self.fields.clone() {
+            let (key, value) = field.key_value();
+            if value.is_present() {
+                w.write_all(if fields == 0 { b" " } else { b"," })?;
+                fields += 1;
+                key.write_key_to(&mut w)?;
+                w.write_all(b"=")?;
+                value.write_value_to(&mut w)?;
+            }
+        }
+        if fields == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "a data point must have at least one field that is not None",
+            ));
+        }
+        if let Some(timestamp) = self.timestamp {
+            w.write_all(b" ")?;
+            timestamp.write_timestamp_to(&mut w)?;
+        }
+        w.write_all(b"\n")
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::writable::{FieldsWritable, TagsWritable};
+    use crate::models::{DataPoint, FieldValue, WriteDataPoint};
+    use crate::writable::{
+        write_measurement_to, DynamicPoint, FieldsWritable, KeyWritable, TagsWritable,
+        TimestampWritable,
+    };
+    use proptest::prelude::*;
+    use std::collections::{BTreeMap, HashMap};
 
     use super::ValueWritable;
 
+    /// Line parsed back from line protocol, with the string field values
+    /// unquoted
+    #[derive(Debug, PartialEq)]
+    struct Line {
+        measurement: String,
+        tags: Vec<(String, String)>,
+        fields: Vec<(String, String)>,
+        timestamp: Option<String>,
+    }
+
+    const KEY_DELIMITERS: &[char] = &[',', '=', ' '];
+
+    /// Parses a line following the escaping rules of
+    /// https://docs.influxdata.com/influxdb/v2.6/reference/syntax/line-protocol/#special-characters
+    fn parse(line: &str) -> Line {
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let (measurement, mut rest) = unescape(line, &[',', ' '], &[',', ' ']);
+
+        let mut tags = vec![];
+        while let Some(tag) = rest.strip_prefix(',') {
+            let (key, tag) = unescape(tag, &['='], KEY_DELIMITERS);
+            let (value, tag) = unescape(&tag[1..], &[',', ' '], KEY_DELIMITERS);
+            tags.push((key, value));
+            rest = tag;
+        }
+
+        let mut fields = vec![];
+        rest = rest.strip_prefix(' ').expect("fields");
+        loop {
+            let (key, field) = unescape(rest, &['='], KEY_DELIMITERS);
+            let field = &field[1..];
+            let (value, field) = match field.strip_prefix('"') {
+                Some(field) => {
+                    let (value, field) = unescape(field, &['"'], &['"', '\\']);
+                    (value, &field[1..])
+                }
+                None => {
+                    let end = field.find(&[',', ' '][..]).unwrap_or(field.len());
+                    (field[..end].to_owned(), &field[end..])
+                }
+            };
+            fields.push((key, value));
+            match field.strip_prefix(',') {
+                Some(field) => rest = field,
+                None => {
+                    rest = field;
+                    break;
+                }
+            }
+        }
+
+        Line {
+            measurement,
+            tags,
+            fields,
+            timestamp: rest.strip_prefix(' ').map(str::to_owned),
+        }
+    }
+
+    /// Reads up to the first unescaped `stops` character, unescaping the
+    /// `escapable` characters
+    fn unescape<'a>(s: &'a str, stops: &[char], escapable: &[char]) -> (String, &'a str) {
+        let mut value = String::new();
+        let mut chars = s.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match chars.peek() {
+                Some((_, next)) if c == '\\' && escapable.contains(next) => {
+                    value.push(*next);
+                    chars.next();
+                }
+                _ if stops.contains(&c) => return (value, &s[i..]),
+                _ => value.push(c),
+            }
+        }
+        (value, "")
+    }
+
+    // Names and tag values ending with a backslash are rejected, as line
+    // protocol cannot tell a trailing one from an escape
+    const NAME: &str = "[a-zA-Z0-9_ ,=\"\\\\]{0,7}[a-zA-Z0-9_ ,=\"]";
+    const STRING: &str = "[a-zA-Z0-9_ ,=\"\\\\]{0,8}";
+
+    proptest! {
+        #[test]
+        fn data_point_round_trip(
+            measurement in "[a-z][a-z ,=\"]{0,8}",
+            tags in prop::collection::btree_map(NAME, NAME, 0..4),
+            fields in prop::collection::btree_map(NAME, STRING, 1..4),
+            timestamp in any::<i64>(),
+        ) {
+            let mut builder = DataPoint::builder(measurement.as_str()).timestamp(timestamp);
+            for (key, value) in &tags {
+                builder = builder.tag(key.as_str(), value.as_str());
+            }
+            for (key, value) in &fields {
+                builder = builder.field(key.as_str(), FieldValue::from(value.as_str()));
+            }
+            let mut line = vec![];
+            builder.build().unwrap().write_data_point_to(&mut line).unwrap();
+
+            prop_assert_eq!(
+                parse(std::str::from_utf8(&line).unwrap()),
+                Line {
+                    measurement,
+                    tags: tags.into_iter().collect(),
+                    fields: fields.into_iter().collect(),
+                    timestamp: Some(timestamp.to_string()),
+                }
+            );
+        }
+
+        #[test]
+        fn writable_round_trip(
+            measurement in "[a-z][a-z ,=\"]{0,8}",
+            tags in (NAME, NAME, NAME, NAME),
+            fields in (NAME, STRING, NAME, STRING),
+        ) {
+            let line = format!(
+                "{},{} {}\n",
+                super::encode_measurement(&measurement),
+                tags.encode_tags(),
+                fields.encode_fields(),
+            );
+
+            let (k1, v1, k2, v2) = tags;
+            let (f1, s1, f2, s2) = fields;
+            prop_assert_eq!(
+                parse(&line),
+                Line {
+                    measurement,
+                    tags: vec![(k1, v1), (k2, v2)],
+                    fields: vec![(f1, s1), (f2, s2)],
+                    timestamp: None,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn none_is_left_out() {
+        let a = ("t1", Some("v1"), "t2", None::<&str>);
+        assert_eq!(a.encode_tags(), "t1=v1");
+        let a = ("f1", None::<i64>, "f2", Some(1.5));
+        assert_eq!(a.encode_fields(), "f2=1.5");
+    }
+
+    #[test]
+    fn timestamps() {
+        use super::TimestampWritable;
+        use chrono::TimeZone;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let t = chrono::Utc.ymd(2001, 9, 9).and_hms(1, 46, 40);
+        assert_eq!(t.encode_timestamp(), "1000000000000000000");
+        let t = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        assert_eq!(t.encode_timestamp(), "1000000000000000000");
+        let t = UNIX_EPOCH - Duration::from_nanos(1);
+        assert_eq!(t.encode_timestamp(), "-1");
+        assert!(!TimestampWritable::is_present(&None::<i64>));
+    }
+
+    #[test]
+    fn trailing_backslash_is_rejected() {
+        let mut line = vec![];
+        let err = ("t1", r"v1\").write_tags_to(&mut line).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(write_measurement_to(r"m\", &mut line).is_err());
+
+        let point = DynamicPoint::new("m", [(r"t1\", "v1")], [("f1", 1.0)]);
+        assert!(point.write_data_point_to(&mut line).is_err());
+    }
+
+    #[test]
+    fn key_writable_escapes() {
+        assert_eq!("a b,c=d".encode_key(), r"a\ b\,c\=d");
+        assert_eq!(r#"say "hi" \o/"#.encode_value(), r#""say \"hi\" \\o/""#);
+        assert_eq!(super::encode_measurement("a b,c=d"), r"a\ b\,c=d");
+    }
+
     #[test]
     fn value_writable_f64() {
         let a: f64 = 33.33;
-        assert_eq!(a.encode_value(), "33.33")
+        assert_eq!(a.encode_value(), "33.33");
+        assert_eq!(1.0.encode_value(), "1");
+        assert_eq!((-0.5).encode_value(), "-0.5");
+    }
+
+    #[test]
+    fn write_to_buffer() {
+        let mut buf = Vec::new();
+        write_measurement_to("cpu load", &mut buf).unwrap();
+        buf.push(b',');
+        ("host", "a").write_tags_to(&mut buf).unwrap();
+        buf.push(b' ');
+        ("usage", 0.5, "cores", 4i64)
+            .write_fields_to(&mut buf)
+            .unwrap();
+        buf.push(b' ');
+        1_000_000_000u64.write_timestamp_to(&mut buf).unwrap();
+        assert_eq!(
+            &buf[..],
+            &b"cpu\\ load,host=a usage=0.5,cores=4i 1000000000"[..]
+        );
     }
 
     #[test]
@@ -214,4 +763,49 @@ mod tests {
         let a = ("ddf", 33u64, "gg", true, "cc", 44.44f64, "dd", 22i64);
         assert_eq!(a.encode_fields(), "ddf=33u,gg=t,cc=44.44,dd=22i");
     }
+
+    #[test]
+    fn collections() {
+        let tags = vec![("host", "a"), ("region", "us west")];
+        assert_eq!(tags.encode_tags(), r"host=a,region=us\ west");
+        assert_eq!(tags[..1].encode_tags(), "host=a");
+        let tags: BTreeMap<_, _> = [
+            ("b", Some("2")),
+            ("a", None),
+            ("c", Some("3")),
+            ("d", Some("")),
+        ]
+        .into();
+        assert_eq!(tags.encode_tags(), "b=2,c=3");
+
+        let fields: HashMap<_, _> = [("usage".to_owned(), 0.5)].into();
+        assert_eq!(fields.encode_fields(), "usage=0.5");
+        let fields = vec![("a", None), ("b", Some(1i64)), ("c", Some(2i64))];
+        assert_eq!(fields.encode_fields(), "b=1i,c=2i");
+    }
+
+    #[test]
+    fn dynamic_point() {
+        let tags = [("host", "a"), ("rack", "")];
+        let fields = BTreeMap::from([("load", 1.5), ("usage", 0.5)]);
+        let point = DynamicPoint::new("cpu", tags.iter().copied(), &fields);
+        assert_eq!(point_to_string(&point), "cpu,host=a load=1.5,usage=0.5\n");
+        assert_eq!(
+            point_to_string(&point.timestamp(10)),
+            "cpu,host=a load=1.5,usage=0.5 10\n"
+        );
+
+        let no_tags: [(&str, &str); 0] = [];
+        let point = DynamicPoint::new("cpu", no_tags, vec![("up", true)]);
+        assert_eq!(point_to_string(&point), "cpu up=t\n");
+
+        let point = DynamicPoint::new("cpu", no_tags, vec![("usage", None::<f64>)]);
+        assert!(point.write_data_point_to(Vec::new()).is_err());
+    }
+
+    fn point_to_string(point: &impl WriteDataPoint) -> String {
+        let mut buf = Vec::new();
+        point.write_data_point_to(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
 }